@@ -0,0 +1,30 @@
+//! v1 载荷 JSON 解析: serde_json 与 simd-json 对比
+//!
+//! ```sh
+//! cargo bench --bench payload_parse --features simd-json
+//! ```
+//!
+//! 不启用 `simd-json` feature 时只运行 serde_json 一组。
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use librustdesk::ticket::TicketPayload;
+
+const PAYLOAD: &[u8] = br#"{"src_id":"123456789","dst_id":"987654321","exp":1700000060,"nonce":"0123456789abcdef","iat":1700000000,"kid":"2024-01","aud":"rustdesk","scopes":["view","control","file"]}"#;
+
+fn bench_payload_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("payload_parse");
+    group.bench_function("serde_json", |b| {
+        b.iter(|| serde_json::from_slice::<TicketPayload>(black_box(PAYLOAD)).unwrap())
+    });
+    #[cfg(feature = "simd-json")]
+    group.bench_function("simd_json", |b| {
+        b.iter(|| {
+            let mut bytes = black_box(PAYLOAD).to_vec();
+            simd_json::serde::from_slice::<TicketPayload>(&mut bytes).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_payload_parse);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+//! 票据验证热路径基准
+//!
+//! ```sh
+//! cargo bench --bench ticket
+//! ```
+//!
+//! 密钥、票据和时间源全部固定, 不同机器和不同提交之间的结果可以直接比较。
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use librustdesk::ticket::{Clock, TicketPayload, TicketSigner, TicketVerifier};
+
+const NOW: i64 = 1_700_000_000;
+const DEVICE_ID: &str = "987654321";
+
+struct FixedClock;
+
+impl Clock for FixedClock {
+    fn now_unix(&self) -> i64 {
+        NOW
+    }
+}
+
+fn signer(seed: u8) -> TicketSigner {
+    TicketSigner::from_secret_hex(&hex::encode([seed; 32])).unwrap()
+}
+
+fn payload() -> TicketPayload {
+    TicketPayload {
+        src_id: "123456789".to_owned(),
+        dst_id: DEVICE_ID.into(),
+        exp: NOW + 600,
+        nonce: "0123456789abcdef".to_owned(),
+        iat: NOW,
+        ..Default::default()
+    }
+}
+
+fn verifier() -> TicketVerifier {
+    let mut verifier = TicketVerifier::new();
+    verifier
+        .set_public_key_hex(&signer(1).verifying_key_hex())
+        .unwrap();
+    verifier.with_clock(Box::new(FixedClock));
+    verifier
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let verifier = verifier();
+    let valid = signer(1).sign(&payload());
+    let forged = signer(2).sign(&payload());
+    assert!(verifier.verify(&valid, DEVICE_ID).is_ok());
+    assert!(verifier.verify(&forged, DEVICE_ID).is_err());
+
+    let mut group = c.benchmark_group("verify");
+    group.bench_function("valid", |b| {
+        b.iter(|| verifier.verify(black_box(&valid), DEVICE_ID))
+    });
+    group.bench_function("prefix_mismatch", |b| {
+        b.iter(|| verifier.verify(black_box("hunter2-not-a-ticket"), DEVICE_ID))
+    });
+    group.bench_function("signature_invalid", |b| {
+        b.iter(|| verifier.verify(black_box(&forged), DEVICE_ID))
+    });
+    group.finish();
+}
+
+fn bench_base64_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("base64_decode");
+    for size in [64usize, 256, 1024, 8 * 1024] {
+        let encoded = URL_SAFE_NO_PAD.encode(vec![0xa5u8; size]);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &encoded, |b, encoded| {
+            b.iter(|| URL_SAFE_NO_PAD.decode(black_box(encoded)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify, bench_base64_decode);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+use docopt::Docopt;
+use librustdesk::ticket::TicketSigner;
+use std::io::Write;
+
+const USAGE: &str = "
+Generate an Ed25519 keypair for signing connection tickets.
+
+Prints the public key (hex) to bake into clients and writes the secret key (hex)
+to <secret-file> for the API Server. The file must not exist yet; on Unix it is
+created readable by the current user only.
+
+Usage:
+  gen_keys <secret-file>
+  gen_keys (-h | --help)
+
+Options:
+  -h --help    Show this screen.
+";
+
+#[derive(Debug, serde::Deserialize)]
+struct Args {
+    arg_secret_file: String,
+}
+
+fn main() {
+    let args: Args = Docopt::new(USAGE)
+        .and_then(|d| d.deserialize())
+        .unwrap_or_else(|e| e.exit());
+
+    let signer = TicketSigner::generate();
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let result = options
+        .open(&args.arg_secret_file)
+        .and_then(|mut file| file.write_all(signer.secret_hex().as_bytes()));
+    if let Err(e) = result {
+        eprintln!("Failed to write {}: {}", args.arg_secret_file, e);
+        std::process::exit(1);
+    }
+
+    println!("{}", signer.verifying_key_hex());
+}
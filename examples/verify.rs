@@ -0,0 +1,77 @@
+use docopt::Docopt;
+use librustdesk::ticket::{decode_payload_unverified, TicketVerifier};
+use std::io::BufRead;
+
+const USAGE: &str = "
+Verify a connection ticket offline and print its claims.
+
+Prints the decoded claims as JSON, then OK or FAILED with the error code.
+Claims of a rejected ticket are decoded without checking the signature and
+must not be trusted. Reads the ticket from stdin when <ticket> is omitted.
+
+Usage:
+  verify [--ignore-expiry] <device-id> <public-key> [<ticket>]
+  verify (-h | --help)
+
+Options:
+  -h --help          Show this screen.
+  --ignore-expiry    Skip exp / nbf / iat checks (offline audit of old tickets).
+";
+
+#[derive(Debug, serde::Deserialize)]
+struct Args {
+    arg_device_id: String,
+    arg_public_key: String,
+    arg_ticket: Option<String>,
+    flag_ignore_expiry: bool,
+}
+
+fn main() {
+    let args: Args = Docopt::new(USAGE)
+        .and_then(|d| d.deserialize())
+        .unwrap_or_else(|e| e.exit());
+
+    let ticket = match args.arg_ticket {
+        Some(ticket) => ticket,
+        None => {
+            let mut line = String::new();
+            if let Err(e) = std::io::stdin().lock().read_line(&mut line) {
+                eprintln!("Failed to read ticket from stdin: {}", e);
+                std::process::exit(2);
+            }
+            line
+        }
+    };
+    let ticket = ticket.trim();
+
+    let mut verifier = TicketVerifier::new();
+    if let Err(e) = verifier.set_public_key_hex(&args.arg_public_key) {
+        eprintln!("Invalid public key: {} ({})", e, e.code());
+        std::process::exit(2);
+    }
+
+    let result = if args.flag_ignore_expiry {
+        verifier.verify_ignore_expiry(ticket, &args.arg_device_id)
+    } else {
+        verifier.verify(ticket, &args.arg_device_id)
+    };
+
+    let claims = match &result {
+        Ok(payload) => Some(payload.clone()),
+        Err(_) => decode_payload_unverified(ticket).ok(),
+    };
+    if let Some(claims) = claims {
+        match serde_json::to_string_pretty(&claims) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to format claims: {}", e),
+        }
+    }
+
+    match result {
+        Ok(_) => println!("OK"),
+        Err(e) => {
+            println!("FAILED {}: {}", e.code(), e);
+            std::process::exit(1);
+        }
+    }
+}
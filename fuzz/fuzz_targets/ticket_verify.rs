@@ -0,0 +1,23 @@
+//! 票据解析模糊测试: 任意输入都只能返回错误, 不能 panic
+//!
+//! 运行: `cargo +nightly fuzz run ticket_verify`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use librustdesk::ticket::{authenticate_password, decode_payload_unverified, TicketVerifier};
+
+/// 种子 [1u8; 32] 对应的公钥; 模糊输入无法伪造该公钥的签名
+const PUBLIC_KEY_HEX: &str = "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c";
+
+fuzz_target!(|data: &[u8]| {
+    let _ = authenticate_password(data, "987654321", PUBLIC_KEY_HEX);
+
+    let Ok(ticket) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut verifier = TicketVerifier::new();
+    verifier.set_public_key_hex(PUBLIC_KEY_HEX).unwrap();
+    assert!(verifier.verify(ticket, "987654321").is_err());
+    let _ = decode_payload_unverified(ticket);
+});
@@ -0,0 +1,520 @@
+//! 免密连接票据验证核心
+//!
+//! 与主程序 `ticket` 模块使用相同的票据格式 (`TICKET:v1:` JSON 载荷, `TICKET:v2:` CBOR 载荷),
+//! 但只依赖 `core` 和 `alloc`, 关闭默认的 `std` feature 后可在没有标准库的固件上使用:
+//!
+//! ```toml
+//! ticket_core = { path = "libs/ticket_core", default-features = false }
+//! ```
+//!
+//! 这类平台通常没有可信的系统时钟, 当前时间由调用方显式传入。
+//! 同样的原因, 启用 `wasm` feature 后可编译到 `wasm32-unknown-unknown`, 供浏览器端
+//! 使用 JS 绑定 (见 `wasm` 模块) 复用同一份验证逻辑。
+//!
+//! 这里的无状态检查与主程序 `TicketVerifier` 的默认配置保持一致: 载荷大小上限、
+//! 有效期与生效时间、签发时间、有效期上限、设备 ID 格式、目标设备 (含数组和前缀模式,
+//! 见 [`DstId`])、nonce 长度, 可调整的部分见 [`VerifyOptions`]。防重放、吊销、受众等
+//! 需要状态或配置的检查由主程序负责; 无法在这里核对的声明 (通道绑定 cb、来源地址
+//! src_cidr、一次性票据 once) 一律拒绝, 委托票据 (dlg) 和哈希形式的目标设备 (dst_id_hash)
+//! 不受支持。
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use core::fmt;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// v1 票据前缀 (JSON 载荷)
+pub const TICKET_PREFIX: &str = "TICKET:v1:";
+/// v2 票据前缀 (CBOR 载荷)
+pub const TICKET_PREFIX_V2: &str = "TICKET:v2:";
+/// 默认载荷解码后的最大字节数, 与主程序一致
+const DEFAULT_MAX_PAYLOAD_BYTES: usize = 8 * 1024;
+/// 签名段 base64 编码后的最大长度 (字符数), 与主程序一致
+const MAX_SIGNATURE_B64_LEN: usize = 1024;
+/// 默认 nonce 最小长度 (字符数)
+const DEFAULT_MIN_NONCE_LEN: usize = 16;
+/// 设备 ID 最大长度 (字符数)
+const DEVICE_ID_MAX_LEN: usize = 64;
+/// 设备 ID 允许的符号 (字母和数字之外)
+const DEVICE_ID_SYMBOLS: &str = "-_.@";
+
+/// 票据的目标设备, 与主程序 `ticket::DstId` 的格式和匹配规则相同
+///
+/// 单个字符串或字符串数组, 每一项可以是设备 ID 或以 `*` 结尾的前缀模式 (如 `site-7-*`);
+/// 单独的 `*` 不匹配任何设备, 数组中任意一项匹配即通过。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DstId {
+    /// 单个设备 ID 或模式
+    One(String),
+    /// 多个设备 ID 或模式
+    Many(Vec<String>),
+}
+
+impl DstId {
+    /// 本机设备 ID 是否匹配
+    pub fn matches(&self, device_id: &str) -> bool {
+        self.entries()
+            .iter()
+            .any(|e| dst_entry_matches(e, device_id))
+    }
+
+    /// 各项 (去掉通配的 `*` 后) 是否都符合设备 ID 格式, 至少要有一项
+    fn is_well_formed(&self) -> bool {
+        let entries = self.entries();
+        !entries.is_empty()
+            && entries
+                .iter()
+                .all(|e| is_device_id(e.strip_suffix('*').unwrap_or(e)))
+    }
+
+    fn entries(&self) -> &[String] {
+        match self {
+            DstId::One(entry) => core::slice::from_ref(entry),
+            DstId::Many(entries) => entries,
+        }
+    }
+}
+
+/// 可调整的检查项, 默认值与主程序 `TicketVerifier::new` 一致
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// 允许的时钟偏差 (秒)
+    pub clock_skew: i64,
+    /// 有效期上限 (exp - iat, 秒), 默认不限制
+    pub max_lifetime: Option<i64>,
+    /// 签发时间窗口: iat 必须在当前时间 ± 该值内; 默认只拒绝 iat 超前当前时间的票据
+    pub iat_window: Option<i64>,
+    /// 载荷解码后的最大字节数
+    pub max_payload_bytes: usize,
+    /// nonce 最小长度 (字符数)
+    pub min_nonce_len: usize,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            clock_skew: 30,
+            max_lifetime: None,
+            iat_window: None,
+            max_payload_bytes: DEFAULT_MAX_PAYLOAD_BYTES,
+            min_nonce_len: DEFAULT_MIN_NONCE_LEN,
+        }
+    }
+}
+
+/// 已验签的票据声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// 主控端设备 ID
+    pub src_id: String,
+    /// 被控端设备 ID 或模式
+    pub dst_id: DstId,
+    /// 过期时间戳 (Unix 秒)
+    pub exp: i64,
+    /// 随机数
+    pub nonce: String,
+    /// 签发时间戳
+    pub iat: i64,
+    /// 生效时间戳
+    #[serde(default)]
+    pub nbf: Option<i64>,
+    /// 通道绑定, 这里无法核对, 携带时拒绝
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cb: Option<String>,
+    /// 允许的来源网络, 这里无法核对, 携带时拒绝
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src_cidr: Option<String>,
+    /// 一次性票据, 需要防重放缓存, 携带时拒绝
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub once: bool,
+}
+
+/// 票据验证错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// 公钥无效
+    InvalidKey,
+    /// 缺少票据前缀
+    BadPrefix,
+    /// 缺少签名分隔符或分隔符多于一个
+    MalformedStructure,
+    /// base64url 解码失败
+    Base64,
+    /// 签名长度不是 64 字节
+    BadSignatureLength(usize),
+    /// 签名验证失败
+    SignatureInvalid,
+    /// 载荷超过大小上限
+    PayloadTooLarge,
+    /// 载荷解析失败
+    PayloadParse,
+    /// 当前时间早于 Unix 纪元
+    ClockError,
+    /// exp 早于 iat
+    TimestampsInconsistent,
+    /// 票据已过期
+    Expired,
+    /// 票据尚未生效
+    NotYetValid,
+    /// 签发时间晚于当前时间
+    IssuedInFuture,
+    /// 签发时间超出窗口
+    IssuedTooFarFromNow,
+    /// 有效期超过上限
+    LifetimeTooLong,
+    /// 设备 ID 格式无效
+    MalformedDeviceId,
+    /// 目标设备 ID 不匹配
+    DeviceMismatch,
+    /// 票据绑定了连接 (cb), 无法核对
+    ChannelBindingMismatch,
+    /// 票据限制了来源地址 (src_cidr), 无法核对
+    AddressNotAllowed,
+    /// nonce 过短
+    WeakNonce,
+    /// 一次性票据需要防重放缓存
+    ReplayCacheRequired,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidKey => write!(f, "公钥无效"),
+            Error::BadPrefix => write!(f, "票据格式无效: 缺少前缀"),
+            Error::MalformedStructure => write!(f, "票据格式无效: 结构错误"),
+            Error::Base64 => write!(f, "base64url 解码失败"),
+            Error::BadSignatureLength(len) => {
+                write!(f, "签名长度无效: 期望 64 字节, 实际 {} 字节", len)
+            }
+            Error::SignatureInvalid => write!(f, "签名验证失败"),
+            Error::PayloadTooLarge => write!(f, "票据载荷过大"),
+            Error::PayloadParse => write!(f, "解析载荷失败"),
+            Error::ClockError => write!(f, "当前时间无效"),
+            Error::TimestampsInconsistent => write!(f, "票据时间戳矛盾: exp 早于 iat"),
+            Error::Expired => write!(f, "票据已过期"),
+            Error::NotYetValid => write!(f, "票据尚未生效"),
+            Error::IssuedInFuture => write!(f, "票据签发时间晚于当前时间"),
+            Error::IssuedTooFarFromNow => write!(f, "票据签发时间超出允许的窗口"),
+            Error::LifetimeTooLong => write!(f, "票据有效期超过上限"),
+            Error::MalformedDeviceId => write!(f, "设备 ID 格式无效"),
+            Error::DeviceMismatch => write!(f, "目标设备 ID 不匹配"),
+            Error::ChannelBindingMismatch => write!(f, "票据绑定的连接无法核对"),
+            Error::AddressNotAllowed => write!(f, "票据限制的来源地址无法核对"),
+            Error::WeakNonce => write!(f, "票据 nonce 过短"),
+            Error::ReplayCacheRequired => write!(f, "一次性票据需要启用防重放缓存"),
+        }
+    }
+}
+
+impl Error {
+    /// 稳定的错误码, 与主程序 `TicketError::code` 的取值一致
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidKey => "KEY_INVALID",
+            Error::BadPrefix => "BAD_PREFIX",
+            Error::MalformedStructure | Error::Base64 => "MALFORMED",
+            Error::BadSignatureLength(_) | Error::SignatureInvalid => "SIG_INVALID",
+            Error::PayloadTooLarge => "TOO_LARGE",
+            Error::PayloadParse => "BAD_PAYLOAD",
+            Error::ClockError => "CLOCK_ERROR",
+            Error::TimestampsInconsistent => "TIMESTAMPS_INCONSISTENT",
+            Error::Expired => "EXPIRED",
+            Error::NotYetValid => "NOT_YET_VALID",
+            Error::IssuedInFuture => "ISSUED_IN_FUTURE",
+            Error::IssuedTooFarFromNow => "IAT_OUT_OF_WINDOW",
+            Error::LifetimeTooLong => "LIFETIME_TOO_LONG",
+            Error::MalformedDeviceId => "MALFORMED_DEVICE_ID",
+            Error::DeviceMismatch => "DEVICE_MISMATCH",
+            Error::ChannelBindingMismatch => "CHANNEL_BINDING_MISMATCH",
+            Error::AddressNotAllowed => "ADDRESS_NOT_ALLOWED",
+            Error::WeakNonce => "WEAK_NONCE",
+            Error::ReplayCacheRequired => "CONFIG",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// 验证票据, 除时钟偏差外使用默认检查项
+///
+/// # 参数
+/// - `public_key`: Ed25519 公钥
+/// - `ticket`: 票据字符串
+/// - `my_device_id`: 本机设备 ID, 必须与 dst_id 匹配
+/// - `now_unix`: 当前 Unix 秒
+/// - `clock_skew`: 允许的时钟偏差 (秒)
+pub fn verify(
+    public_key: &[u8; 32],
+    ticket: &str,
+    my_device_id: &str,
+    now_unix: i64,
+    clock_skew: i64,
+) -> Result<Claims, Error> {
+    let options = VerifyOptions {
+        clock_skew,
+        ..Default::default()
+    };
+    verify_with_options(public_key, ticket, my_device_id, now_unix, &options)
+}
+
+/// 按 `options` 验证票据, 参数含义同 [`verify`]
+pub fn verify_with_options(
+    public_key: &[u8; 32],
+    ticket: &str,
+    my_device_id: &str,
+    now_unix: i64,
+    options: &VerifyOptions,
+) -> Result<Claims, Error> {
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| Error::InvalidKey)?;
+
+    let (cbor, content) = if let Some(content) = ticket.strip_prefix(TICKET_PREFIX) {
+        (false, content)
+    } else if let Some(content) = ticket.strip_prefix(TICKET_PREFIX_V2) {
+        (true, content)
+    } else {
+        return Err(Error::BadPrefix);
+    };
+    let (payload_b64, signature_b64) = content
+        .split_once('.')
+        .filter(|(_, sig)| !sig.contains('.'))
+        .ok_or(Error::MalformedStructure)?;
+
+    // 解码前检查长度, 避免为超大输入分配内存
+    if b64_decoded_len(payload_b64.len()) > options.max_payload_bytes {
+        return Err(Error::PayloadTooLarge);
+    }
+    if signature_b64.len() > MAX_SIGNATURE_B64_LEN {
+        return Err(Error::MalformedStructure);
+    }
+
+    let payload_bytes = b64_decode(payload_b64)?;
+    let signature_bytes = b64_decode(signature_b64)?;
+    let signature: [u8; 64] = signature_bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::BadSignatureLength(signature_bytes.len()))?;
+    key.verify(&payload_bytes, &Signature::from_bytes(&signature))
+        .map_err(|_| Error::SignatureInvalid)?;
+
+    let claims: Claims = if cbor {
+        ciborium::from_reader(payload_bytes.as_slice()).map_err(|_| Error::PayloadParse)?
+    } else {
+        serde_json::from_slice(&payload_bytes).map_err(|_| Error::PayloadParse)?
+    };
+
+    check_claims(&claims, my_device_id, now_unix, options)?;
+    Ok(claims)
+}
+
+/// 无状态声明检查, 顺序与主程序 `TicketVerifier` 相同
+fn check_claims(
+    claims: &Claims,
+    my_device_id: &str,
+    now: i64,
+    options: &VerifyOptions,
+) -> Result<(), Error> {
+    let skew = options.clock_skew;
+    // 时间字段来自签发方, 运算一律饱和
+    if now < 0 {
+        return Err(Error::ClockError);
+    }
+    if claims.exp < claims.iat {
+        return Err(Error::TimestampsInconsistent);
+    }
+    if claims.exp < now.saturating_sub(skew) {
+        return Err(Error::Expired);
+    }
+    if let Some(nbf) = claims.nbf {
+        if now.saturating_add(skew) < nbf {
+            return Err(Error::NotYetValid);
+        }
+    }
+    if let Some(window) = options.iat_window {
+        if claims.iat.abs_diff(now) > window as u64 {
+            return Err(Error::IssuedTooFarFromNow);
+        }
+    } else if claims.iat > now.saturating_add(skew) {
+        return Err(Error::IssuedInFuture);
+    }
+    if let Some(max_lifetime) = options.max_lifetime {
+        if claims.exp.saturating_sub(claims.iat) > max_lifetime {
+            return Err(Error::LifetimeTooLong);
+        }
+    }
+    if !is_device_id(&claims.src_id) || !claims.dst_id.is_well_formed() {
+        return Err(Error::MalformedDeviceId);
+    }
+    if !claims.dst_id.matches(my_device_id) {
+        return Err(Error::DeviceMismatch);
+    }
+    if claims.cb.is_some() {
+        return Err(Error::ChannelBindingMismatch);
+    }
+    if claims.src_cidr.is_some() {
+        return Err(Error::AddressNotAllowed);
+    }
+    if claims.nonce.chars().count() < options.min_nonce_len {
+        return Err(Error::WeakNonce);
+    }
+    if claims.once {
+        return Err(Error::ReplayCacheRequired);
+    }
+    Ok(())
+}
+
+/// 设备 ID 格式: 非空, 不超过 64 个字符, 只含 ASCII 字母、数字和 `-_.@`
+fn is_device_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= DEVICE_ID_MAX_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || DEVICE_ID_SYMBOLS.contains(c))
+}
+
+fn dst_entry_matches(entry: &str, device_id: &str) -> bool {
+    match entry.strip_suffix('*') {
+        Some("") => false,
+        Some(prefix) => device_id
+            .as_bytes()
+            .get(..prefix.len())
+            .is_some_and(|head| head.ct_eq(prefix.as_bytes()).into()),
+        None => entry.as_bytes().ct_eq(device_id.as_bytes()).into(),
+    }
+}
+
+/// 无填充 base64 编码长度对应的解码后字节数: 每 4 个字符对应 3 个字节
+fn b64_decoded_len(encoded_len: usize) -> usize {
+    encoded_len / 4 * 3 + encoded_len % 4 * 3 / 4
+}
+
+fn b64_decode(segment: &str) -> Result<Vec<u8>, Error> {
+    URL_SAFE_NO_PAD.decode(segment).map_err(|_| Error::Base64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn sign(key: &SigningKey, payload: &[u8]) -> String {
+        let signature = key.sign(payload);
+        format!(
+            "{}{}.{}",
+            TICKET_PREFIX,
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    #[test]
+    fn test_verify() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let other_key = SigningKey::from_bytes(&[2u8; 32]).verifying_key().to_bytes();
+        let payload = br#"{"src_id":"123456789","dst_id":"987654321","exp":1060,"nonce":"0123456789abcdef","iat":1000,"kid":"k1"}"#;
+        let ticket = sign(&key, payload);
+
+        let claims = verify(&public_key, &ticket, "987654321", 1_000, 30).unwrap();
+        assert_eq!(claims.src_id, "123456789");
+        assert_eq!(
+            verify(&public_key, &ticket, "111111111", 1_000, 30).unwrap_err(),
+            Error::DeviceMismatch
+        );
+        assert_eq!(
+            verify(&public_key, &ticket, "987654321", 1_091, 30).unwrap_err(),
+            Error::Expired
+        );
+        assert_eq!(
+            verify(&other_key, &ticket, "987654321", 1_000, 30).unwrap_err(),
+            Error::SignatureInvalid
+        );
+        assert_eq!(
+            verify(&public_key, "TICKET:v1:abc", "987654321", 1_000, 30).unwrap_err(),
+            Error::MalformedStructure
+        );
+        assert_eq!(
+            verify(&public_key, "password", "987654321", 1_000, 30).unwrap_err(),
+            Error::BadPrefix
+        );
+    }
+
+    #[test]
+    fn test_stateless_checks() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let check_at = |claims: &str, device_id: &str, now: i64, options: &VerifyOptions| {
+            let payload = format!(
+                r#"{{"src_id":"123456789","exp":1060,"iat":1000,"nonce":"0123456789abcdef",{}}}"#,
+                claims
+            );
+            verify_with_options(
+                &public_key,
+                &sign(&key, payload.as_bytes()),
+                device_id,
+                now,
+                options,
+            )
+        };
+        let check = |claims: &str, device_id: &str, options: &VerifyOptions| {
+            check_at(claims, device_id, 1_000, options)
+        };
+        let defaults = VerifyOptions::default();
+
+        // 数组和前缀模式的 dst_id
+        let multi = r#""dst_id":["111111111","site-7-*"]"#;
+        assert!(check(multi, "111111111", &defaults).is_ok());
+        assert!(check(multi, "site-7-042", &defaults).is_ok());
+        assert_eq!(
+            check(multi, "site-8-042", &defaults).unwrap_err(),
+            Error::DeviceMismatch
+        );
+        // 单独的 `*` 与主程序一样按格式无效拒绝
+        assert_eq!(
+            check(r#""dst_id":"*""#, "987654321", &defaults).unwrap_err(),
+            Error::MalformedDeviceId
+        );
+        assert_eq!(
+            check(r#""dst_id":"bad id""#, "bad id", &defaults).unwrap_err(),
+            Error::MalformedDeviceId
+        );
+
+        let dst = r#""dst_id":"987654321""#;
+        let options = VerifyOptions {
+            max_lifetime: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            check(dst, "987654321", &options).unwrap_err(),
+            Error::LifetimeTooLong
+        );
+        assert_eq!(
+            check_at(dst, "987654321", 960, &defaults).unwrap_err(),
+            Error::IssuedInFuture
+        );
+        let options = VerifyOptions {
+            max_payload_bytes: 16,
+            ..Default::default()
+        };
+        assert_eq!(
+            check(dst, "987654321", &options).unwrap_err(),
+            Error::PayloadTooLarge
+        );
+        let bound = r#""dst_id":"987654321","cb":"abc""#;
+        assert_eq!(
+            check(bound, "987654321", &defaults).unwrap_err(),
+            Error::ChannelBindingMismatch
+        );
+    }
+}
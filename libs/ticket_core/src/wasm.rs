@@ -0,0 +1,120 @@
+//! 浏览器端票据验证的 JS 绑定
+//!
+//! 浏览器中没有可信的系统时钟可供 Rust 直接读取, 当前时间由 JS 传入 (通常为
+//! `Date.now() / 1000`)。
+//!
+//! ```js
+//! const result = verify_ticket(ticket, deviceId, pubkeyHex, Date.now() / 1000);
+//! if (result.ok) {
+//!     console.log(result.claims.src_id);
+//! } else {
+//!     console.warn(result.code, result.message);
+//! }
+//! ```
+
+use crate::{verify, Claims};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// 允许的时钟偏差 (秒), 与主程序 `TicketVerifier` 的默认值一致
+const CLOCK_SKEW: i64 = 30;
+
+/// 返回给 JS 的验证结果
+#[derive(Debug, Serialize)]
+struct Outcome {
+    ok: bool,
+    /// 验证成功时的票据声明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    claims: Option<Claims>,
+    /// 验证失败时的错误码, 见 [`Error::code`](crate::Error::code)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    /// 验证失败时的错误描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl Outcome {
+    fn failure(code: &'static str, message: String) -> Self {
+        Self {
+            ok: false,
+            claims: None,
+            code: Some(code),
+            message: Some(message),
+        }
+    }
+}
+
+/// 验证票据, 返回 `{ ok: true, claims }` 或 `{ ok: false, code, message }`
+///
+/// - `pubkey_hex`: 十六进制编码的 Ed25519 公钥
+/// - `now_unix`: 当前 Unix 秒, 可以带小数部分
+#[wasm_bindgen]
+pub fn verify_ticket(ticket: &str, device_id: &str, pubkey_hex: &str, now_unix: f64) -> JsValue {
+    let outcome = check(ticket, device_id, pubkey_hex, now_unix);
+    serde_wasm_bindgen::to_value(&outcome).unwrap_or(JsValue::NULL)
+}
+
+fn check(ticket: &str, device_id: &str, pubkey_hex: &str, now_unix: f64) -> Outcome {
+    if !now_unix.is_finite() {
+        return Outcome::failure("CLOCK_ERROR", "当前时间无效".to_string());
+    }
+    let key: Option<[u8; 32]> = hex::decode(pubkey_hex.trim())
+        .ok()
+        .and_then(|bytes: Vec<u8>| bytes.try_into().ok());
+    let Some(key) = key else {
+        let e = crate::Error::InvalidKey;
+        return Outcome::failure(e.code(), e.to_string());
+    };
+    match verify(&key, ticket, device_id, now_unix as i64, CLOCK_SKEW) {
+        Ok(claims) => Outcome {
+            ok: true,
+            claims: Some(claims),
+            code: None,
+            message: None,
+        },
+        Err(e) => Outcome::failure(e.code(), e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TICKET_PREFIX;
+    use alloc::format;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_check() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let pubkey_hex = hex::encode(key.verifying_key().to_bytes());
+        let payload = br#"{"src_id":"123456789","dst_id":"987654321","exp":1060,"nonce":"0123456789abcdef","iat":1000}"#;
+        let ticket = format!(
+            "{}{}.{}",
+            TICKET_PREFIX,
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(key.sign(payload).to_bytes())
+        );
+
+        let outcome = check(&ticket, "987654321", &pubkey_hex, 1_000.5);
+        assert!(outcome.ok);
+        assert_eq!(outcome.claims.unwrap().src_id, "123456789");
+        assert_eq!(
+            check(&ticket, "987654321", &pubkey_hex, 2_000.0).code,
+            Some("EXPIRED")
+        );
+        assert_eq!(
+            check(&ticket, "987654321", "abcd", 1_000.0).code,
+            Some("KEY_INVALID")
+        );
+        assert_eq!(
+            check(&ticket, "987654321", &pubkey_hex, f64::NAN).code,
+            Some("CLOCK_ERROR")
+        );
+    }
+}
@@ -2,20 +2,101 @@
 //!
 //! 该模块实现了对 API Server 签发的免密连接票据的验证逻辑。
 //! 使用 Ed25519 签名算法进行离线验签。
+//!
+//! 没有标准库的嵌入式设备可使用 `libs/ticket_core` 中的 no_std 验证核心 (相同票据格式, 不支持压缩载荷);
+//! 浏览器端通过其 `wasm` feature 提供的 JS 绑定验证。
 
-use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use base64::{
+    engine::{
+        general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD},
+        GeneralPurpose,
+    },
+    Engine,
+};
 use crate::hbbs_http::{create_http_client_with_url, HbbHttpResponse};
-use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use cache::VerifyCache;
+use cidr_utils::cidr::IpCidr;
+use ed25519_dalek::{Signature, VerifyingKey};
+use flate2::read::DeflateDecoder;
 use hbb_common::{
     config::{keys, Config, LocalConfig},
     log,
 };
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    cell::{Cell, OnceCell},
+    io::{BufRead, Read},
+    net::IpAddr,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use subtle::ConstantTimeEq;
+
+mod builder;
+mod cache;
+pub mod ffi;
+#[cfg(feature = "key-fetch")]
+mod key_set;
+mod rate_limit;
+mod replay;
+mod revocation;
+mod scheme;
+mod signer;
+#[cfg(feature = "rusqlite")]
+mod sqlite_replay;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod watch;
+pub use builder::TicketVerifierBuilder;
+pub use rate_limit::RateLimiter;
+pub use replay::{ReplayCache, ReplayStore};
+pub use revocation::RevocationList;
+pub use scheme::{Ed25519Scheme, SignatureScheme, ALG_ED25519, ALG_ED25519PH};
+pub use signer::TicketSigner;
+pub use watch::TicketWatch;
+#[cfg(feature = "rusqlite")]
+pub use sqlite_replay::SqliteReplayStore;
+
+/// 票据模块日志的 target, 运维可据此单独路由票据日志
+const LOG_TARGET: &str = "rs_client::ticket";
 
 /// 票据前缀
 const TICKET_PREFIX: &str = "TICKET:v1:";
+/// v2 票据前缀 (CBOR 载荷)
+const TICKET_PREFIX_V2: &str = "TICKET:v2:";
+/// 票据前缀中版本标记之前的部分
+const DEFAULT_PREFIX_BASE: &str = "TICKET:";
+/// v2 载荷段以该字符开头表示载荷经过 DEFLATE 压缩 (该字符不属于 base64url 字母表)
+const COMPRESSED_MARKER: char = '~';
 const TICKET_PUBLIC_KEY_OPTION: &str = "ticket-public-key";
+/// 默认允许的时钟偏差 (秒)
+const DEFAULT_CLOCK_SKEW_SECS: i64 = 30;
+/// Ed25519 SubjectPublicKeyInfo 的 DER 前缀 (RFC 8410), 其后紧跟 32 字节公钥
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+/// 默认载荷解码后的最大字节数
+const DEFAULT_MAX_PAYLOAD_BYTES: usize = 8 * 1024;
+/// 签名段 base64 编码后的最大长度 (字符数)
+///
+/// Ed25519 签名编码后为 86 个字符, 精确长度在解码后检查 ([`TicketError::BadSignatureLength`]);
+/// 这里留出余量兼容自定义签名算法 (如 RSA-4096 签名约 683 个字符), 只用于在解码前
+/// 拒绝明显异常的输入。
+const MAX_SIGNATURE_B64_LEN: usize = 1024;
+/// [`TicketVerifier::verify_reader`] 每行在最长票据之外允许的首尾空白字节数
+const LINE_WHITESPACE_SLACK: usize = 64;
+/// 默认 nonce 最小长度 (字符数)
+const DEFAULT_MIN_NONCE_LEN: usize = 16;
+/// 设备 ID 默认最大长度 (字符数)
+const DEFAULT_DEVICE_ID_MAX_LEN: usize = 64;
+/// 设备 ID 默认允许的符号 (字母和数字之外)
+const DEFAULT_DEVICE_ID_SYMBOLS: &str = "-_.@";
 
 #[derive(Debug, Deserialize)]
 struct TicketPublicKeyResponse {
@@ -29,30 +110,1182 @@ struct TicketResponse {
     pub expires_in: i64,
 }
 
+/// JWKS 文档, 见 [`TicketVerifier::load_jwks`]
+#[derive(Debug, Deserialize)]
+struct JwksDocument {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 struct TicketRequest {
     target_id: String,
 }
 
+/// 常用权限范围: 完整控制
+pub const SCOPE_CONTROL: &str = "control";
+/// 常用权限范围: 仅查看
+pub const SCOPE_VIEW: &str = "view";
+/// 常用权限范围: 文件传输
+pub const SCOPE_FILE: &str = "file";
+/// 常用权限范围: 剪贴板
+pub const SCOPE_CLIPBOARD: &str = "clipboard";
+
 /// 票据载荷结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `Debug` 输出隐去 nonce 的大部分内容, 需要完整内容时使用 [`TicketPayload::full_debug`]。
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct TicketPayload {
     /// 主控端设备 ID
     pub src_id: String,
-    /// 被控端设备 ID  
-    pub dst_id: String,
+    /// 被控端设备 ID (可以是多个设备或通配模式, 见 [`DstId`]); 携带 dst_id_hash 时忽略
+    #[serde(default)]
+    pub dst_id: DstId,
     /// 过期时间戳 (Unix 秒)
     pub exp: i64,
     /// 随机数
     pub nonce: String,
     /// 签发时间戳
     pub iat: i64,
+    /// 签名公钥 ID (可选, 存在时只使用对应公钥验签)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// 签名算法 (可选, 缺省为 [`ALG_ED25519`]), 只使用支持该算法的公钥验签
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    /// 生效时间戳 (可选, 在此之前票据不可用)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// 受众 (可选, 票据适用的服务, 可以是多个, 见 [`Audience`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// 权限范围 (如 [`SCOPE_VIEW`], [`SCOPE_FILE`]), 为空表示未限定
+    ///
+    /// 验证器不据此拒绝票据, 由连接层根据返回的载荷自行限制权限
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    /// 一次性票据: 有效期内也只能通过一次验证, 要求验证器启用防重放缓存
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub once: bool,
+    /// 通道绑定值 (可选, 见 [`channel_binding`]), 存在时票据只能用于该连接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cb: Option<String>,
+    /// 票据类型 (可选, 如连接票据与设备配置令牌共用同一签发机制时用于区分)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    /// 允许的主控端网络 (可选, CIDR 如 `203.0.113.0/24`、`2001:db8::/32`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src_cidr: Option<String>,
+    /// 委托凭据 (可选, 见 [`Delegation`]), 存在时票据由其中的中间公钥签名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dlg: Option<Delegation>,
+    /// 是否可以在到期前向 API Server 换取新票据 (可选, 缺省视为不可续期)
+    ///
+    /// 验证器只原样返回, 不据此接受或拒绝票据, 见 [`is_renewable`](Self::is_renewable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewable: Option<bool>,
+    /// 续期地址 (可选)
+    ///
+    /// 验证器不访问该地址。地址来自票据载荷, 即使签名有效也只代表签发方的声明:
+    /// 客户端续期前应确认其为 HTTPS 且主机属于已配置的 API Server, 不要向任意地址
+    /// 发送凭据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renew_url: Option<String>,
+    /// 加盐哈希后的被控端设备 ID (可选, 算法见 [`hash_device_id`]), 签发方不愿在票据中
+    /// 暴露设备 ID 时代替 dst_id; 存在时必须同时携带 dst_salt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dst_id_hash: Option<String>,
+    /// dst_id_hash 使用的盐
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dst_salt: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl TicketPayload {
+    /// 票据是否声明可续期, 未携带 renewable 时为 false
+    pub fn is_renewable(&self) -> bool {
+        self.renewable.unwrap_or(false)
+    }
+
+    /// 票据是否发给本机设备: 携带 dst_id_hash 时比较哈希, 否则按 dst_id 匹配
+    fn targets(&self, device_id: &str) -> bool {
+        match (self.dst_id_hash.as_deref(), self.dst_salt.as_deref()) {
+            (Some(hash), Some(salt)) => {
+                ct_eq(&hash.to_ascii_lowercase(), &hash_device_id(salt, device_id))
+            }
+            (Some(_), None) => false,
+            (None, _) => self.dst_id.matches(device_id),
+        }
+    }
+
+    /// 是否包含指定权限范围 (完全匹配)
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    /// 票据指纹, 用于在客户端日志、服务端日志和签发记录之间关联同一张票据
+    ///
+    /// 取 src_id、dst_id、iat、nonce 四个字段按 [`canonical_json`] 规则 (键按字节序排列,
+    /// 无多余空白) 序列化后的 SHA-256, 输出前 8 字节的十六进制。服务端用签名所用的同一套
+    /// 规范化即可复现, 日志中记录指纹而不是票据本身 (票据是凭据)。
+    pub fn fingerprint(&self) -> String {
+        let value = serde_json::json!({
+            "src_id": self.src_id,
+            "dst_id": self.dst_id,
+            "iat": self.iat,
+            "nonce": self.nonce,
+        });
+        let mut canonical = Vec::new();
+        write_canonical(&value, &mut canonical);
+        hex::encode(&Sha256::digest(canonical)[..8])
+    }
+
+    /// 不隐去 nonce 的完整 `Debug` 输出, 仅在运维明确需要时使用
+    pub fn full_debug(&self) -> impl std::fmt::Debug + '_ {
+        FullDebug(self)
+    }
+
+    fn fmt_debug(&self, f: &mut std::fmt::Formatter<'_>, redact_nonce: bool) -> std::fmt::Result {
+        let nonce = if redact_nonce {
+            redact(&self.nonce)
+        } else {
+            self.nonce.clone()
+        };
+        f.debug_struct("TicketPayload")
+            .field("src_id", &self.src_id)
+            .field("dst_id", &self.dst_id)
+            .field("exp", &self.exp)
+            .field("nonce", &nonce)
+            .field("iat", &self.iat)
+            .field("kid", &self.kid)
+            .field("alg", &self.alg)
+            .field("nbf", &self.nbf)
+            .field("aud", &self.aud)
+            .field("scopes", &self.scopes)
+            .field("once", &self.once)
+            .field("cb", &self.cb)
+            .field("typ", &self.typ)
+            .field("src_cidr", &self.src_cidr)
+            .field("dlg", &self.dlg)
+            .field("renewable", &self.renewable)
+            .field("renew_url", &self.renew_url)
+            .field("dst_id_hash", &self.dst_id_hash)
+            .field("dst_salt", &self.dst_salt)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for TicketPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_debug(f, true)
+    }
+}
+
+/// 单行摘要, 如 `ticket src=123456789 dst=987654321 exp=1700000060 (in 60s)`
+impl std::fmt::Display for TicketPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let remaining = self.exp.saturating_sub(unix_time(SystemTime::now()));
+        write!(f, "ticket src={} dst={} exp={} ", self.src_id, self.dst_id, self.exp)?;
+        if remaining >= 0 {
+            write!(f, "(in {}s)", remaining)
+        } else {
+            write!(f, "(expired {}s ago)", remaining.saturating_neg())
+        }
+    }
+}
+
+struct FullDebug<'a>(&'a TicketPayload);
+
+impl std::fmt::Debug for FullDebug<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt_debug(f, false)
+    }
+}
+
+/// 只保留前 4 个字符, 其余以 `…` 代替
+fn redact(value: &str) -> String {
+    let visible: String = value.chars().take(4).collect();
+    if visible.len() < value.len() {
+        format!("{}…", visible)
+    } else {
+        visible
+    }
+}
+
+/// 票据的目标设备
+///
+/// 序列化为单个字符串或字符串数组, 单个字符串时与旧格式完全相同。
+/// 每一项可以是设备 ID 或以 `*` 结尾的前缀模式 (如 `site-7-*`), 匹配规则:
+/// - 不以 `*` 结尾的项要求与本机设备 ID 完全相同;
+/// - 以 `*` 结尾的项匹配以 `*` 之前内容开头的设备 ID, `*` 只在末尾有通配含义;
+/// - 单独的 `*` 不匹配任何设备, 接受任意设备必须由验证器显式配置 [`DeviceBinding::AnyDevice`];
+/// - 数组中任意一项匹配即通过。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DstId {
+    /// 单个设备 ID 或模式
+    One(String),
+    /// 多个设备 ID 或模式
+    Many(Vec<String>),
+}
+
+impl DstId {
+    /// 本机设备 ID 是否匹配
+    pub fn matches(&self, device_id: &str) -> bool {
+        match self {
+            DstId::One(entry) => dst_entry_matches(entry, device_id),
+            DstId::Many(entries) => entries.iter().any(|e| dst_entry_matches(e, device_id)),
+        }
+    }
+
+    /// 各项 (去掉通配的 `*` 后) 是否都符合设备 ID 格式, 至少要有一项
+    fn is_well_formed(&self, format: &DeviceIdFormat) -> bool {
+        let entry_ok = |entry: &str| format.accepts(entry.strip_suffix('*').unwrap_or(entry));
+        match self {
+            DstId::One(entry) => entry_ok(entry),
+            DstId::Many(entries) => !entries.is_empty() && entries.iter().all(|e| entry_ok(e)),
+        }
+    }
+}
+
+impl Default for DstId {
+    fn default() -> Self {
+        DstId::One(String::new())
+    }
+}
+
+impl From<&str> for DstId {
+    fn from(id: &str) -> Self {
+        DstId::One(id.to_owned())
+    }
+}
+
+impl From<String> for DstId {
+    fn from(id: String) -> Self {
+        DstId::One(id)
+    }
+}
+
+impl From<Vec<String>> for DstId {
+    fn from(ids: Vec<String>) -> Self {
+        DstId::Many(ids)
+    }
+}
+
+impl PartialEq<&str> for DstId {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, DstId::One(id) if id == other)
+    }
+}
+
+/// 多个目标以逗号分隔
+impl std::fmt::Display for DstId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DstId::One(id) => f.write_str(id),
+            DstId::Many(ids) => f.write_str(&ids.join(",")),
+        }
+    }
+}
+
+/// 设备 ID 格式: 非空, 不超过 `max_len` 个字符, 只含 ASCII 字母、数字和 `symbols` 中的符号
+#[derive(Clone)]
+struct DeviceIdFormat {
+    max_len: usize,
+    symbols: String,
+}
+
+impl Default for DeviceIdFormat {
+    fn default() -> Self {
+        Self {
+            max_len: DEFAULT_DEVICE_ID_MAX_LEN,
+            symbols: DEFAULT_DEVICE_ID_SYMBOLS.to_owned(),
+        }
+    }
+}
+
+impl DeviceIdFormat {
+    fn accepts(&self, id: &str) -> bool {
+        !id.is_empty()
+            && id.len() <= self.max_len
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || self.symbols.contains(c))
+    }
+}
+
+/// 票据受众: 单个字符串或字符串数组
+///
+/// 票据同时适用于多个服务时使用数组, 验证器期望的受众是其中之一即通过。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    /// 单个受众
+    One(String),
+    /// 多个受众
+    Many(Vec<String>),
+}
+
+impl Audience {
+    /// 是否包含指定受众
+    pub fn contains(&self, aud: &str) -> bool {
+        match self {
+            Audience::One(one) => one == aud,
+            Audience::Many(many) => many.iter().any(|a| a == aud),
+        }
+    }
+}
+
+impl From<&str> for Audience {
+    fn from(aud: &str) -> Self {
+        Audience::One(aud.to_owned())
+    }
+}
+
+impl From<String> for Audience {
+    fn from(aud: String) -> Self {
+        Audience::One(aud)
+    }
+}
+
+impl From<Vec<String>> for Audience {
+    fn from(auds: Vec<String>) -> Self {
+        Audience::Many(auds)
+    }
+}
+
+impl PartialEq<&str> for Audience {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Audience::One(aud) if aud == other)
+    }
+}
+
+/// 多个受众以逗号分隔
+impl std::fmt::Display for Audience {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Audience::One(aud) => f.write_str(aud),
+            Audience::Many(auds) => f.write_str(&auds.join(",")),
+        }
+    }
+}
+
+/// 委托凭据: 信任锚私钥为短期中间公钥签发的授权
+///
+/// API Server 用短期中间私钥签发票据, 并在载荷中附带该凭据; 客户端只需配置信任锚公钥
+/// ([`TicketVerifier::add_trust_anchor_hex`]), 中间私钥无需分发。委托只有一级,
+/// 中间公钥不能再委托其他公钥。信任锚签名覆盖的内容见 [`delegation_message`]。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delegation {
+    /// 中间公钥 (Ed25519, 十六进制)
+    pub key: String,
+    /// 中间公钥的过期时间戳 (Unix 秒), 票据的 exp 不能晚于该时刻
+    pub exp: i64,
+    /// 信任锚对 [`delegation_message`] 的 Ed25519 签名 (base64url, 无填充)
+    pub sig: String,
+}
+
+/// 信任锚为中间公钥签名的内容: `TICKET-DELEGATION:v1:` || 32 字节公钥 || exp (大端 i64)
+pub fn delegation_message(key: &[u8; 32], exp: i64) -> Vec<u8> {
+    let mut msg = b"TICKET-DELEGATION:v1:".to_vec();
+    msg.extend_from_slice(key);
+    msg.extend_from_slice(&exp.to_be_bytes());
+    msg
+}
+
+fn dst_entry_matches(entry: &str, device_id: &str) -> bool {
+    match entry.strip_suffix('*') {
+        Some("") => false,
+        Some(prefix) => device_id
+            .as_bytes()
+            .get(..prefix.len())
+            .is_some_and(|head| head.ct_eq(prefix.as_bytes()).into()),
+        None => ct_eq(entry, device_id),
+    }
+}
+
+/// 票据验证错误
+#[derive(Debug)]
+pub enum TicketError {
+    /// 未设置公钥
+    MissingPublicKey,
+    /// 公钥十六进制解码失败
+    KeyHex(hex::FromHexError),
+    /// 公钥长度无效 (实际字节数)
+    BadKeyLength(usize),
+    /// 公钥不是合法的 Ed25519 点
+    InvalidKey(ed25519_dalek::SignatureError),
+    /// PEM 公钥格式无效或不是 Ed25519 公钥
+    InvalidPem(String),
+    /// 缺少票据前缀
+    BadPrefix,
+    /// 输入为空、只有空白或只有前缀
+    EmptyTicket,
+    /// 缺少载荷与签名之间的分隔符
+    MissingSeparator,
+    /// 票据结构无效 (如包含多个分隔符)
+    MalformedStructure,
+    /// 载荷超过大小上限
+    PayloadTooLarge,
+    /// base64url 解码失败
+    Base64(base64::DecodeError),
+    /// 签名长度无效 (实际字节数)
+    BadSignatureLength(usize),
+    /// 签名验证失败
+    SignatureInvalid,
+    /// 载荷 JSON 解析失败
+    PayloadParse(serde_json::Error),
+    /// 载荷 CBOR 解析失败
+    PayloadCbor(ciborium::de::Error<std::io::Error>),
+    /// 票据已过期
+    Expired,
+    /// 目标设备 ID 不匹配
+    DeviceMismatch { expected: String, actual: String },
+    /// 票据指定的公钥 ID 未加载
+    UnknownKeyId(String),
+    /// 票据 nonce 已被使用
+    ReplayDetected,
+    /// 验证器配置无效
+    ConfigurationError(String),
+    /// 票据尚未生效
+    NotYetValid,
+    /// 签发时间晚于当前时间
+    IssuedInFuture,
+    /// 票据有效期 (exp - iat) 超过上限
+    LifetimeTooLong,
+    /// 过期时间距当前时间过远
+    ExpTooFar,
+    /// 系统时钟异常 (早于 Unix 纪元)
+    ClockError,
+    /// 受众不匹配或缺失
+    AudienceMismatch,
+    /// 票据已被吊销
+    Revoked,
+    /// nonce 为空或过短, 不足以保证唯一性
+    WeakNonce,
+    /// 从 API Server 获取公钥集合失败
+    KeyFetch(String),
+    /// 一次性票据已被使用
+    AlreadyUsed,
+    /// 压缩载荷解压失败
+    Decompress(std::io::Error),
+    /// 通道绑定值不匹配, 票据签发给了其他连接
+    ChannelBindingMismatch,
+    /// 签发时间超出允许的窗口 (过早或过晚)
+    IssuedTooFarFromNow,
+    /// 签发时间早于统一失效的截止时间
+    IssuedBeforeCutoff,
+    /// 过期时间早于签发时间
+    TimestampsInconsistent,
+    /// 读取票据输入失败
+    Io(std::io::Error),
+    /// 票据类型不匹配或缺失
+    WrongTicketType,
+    /// 该主控端 ID 验证失败次数过多, 处于冷却期
+    RateLimited,
+    /// 对端地址不在票据允许的网络内
+    AddressNotAllowed,
+    /// 追加的公钥已经加载过 (仅在启用重复检查时返回)
+    DuplicateKey,
+    /// 委托凭据无效 (信任锚签名错误、中间公钥无效或票据有效期超出委托范围)
+    DelegationInvalid,
+    /// 委托的中间公钥已过期
+    DelegationExpired,
+    /// 同一公钥 ID 已对应另一个公钥
+    KeyIdConflict(String),
+    /// src_id 或 dst_id 为空或格式不合法
+    MalformedDeviceId,
+    /// 载荷包含未知字段 (启用 [`TicketVerifier::with_deny_unknown_fields`] 时)
+    UnknownField(String),
+    /// 缺少必需的声明 (启用 [`TicketVerifier::with_strict_claims`] 时)
+    MissingRequiredClaim(&'static str),
+}
+
+impl std::fmt::Display for TicketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TicketError::MissingPublicKey => write!(f, "公钥未设置"),
+            TicketError::KeyHex(e) => write!(f, "解码公钥失败: {}", e),
+            TicketError::BadKeyLength(len) => {
+                write!(f, "公钥长度无效: 期望 32 字节, 实际 {} 字节", len)
+            }
+            TicketError::InvalidKey(e) => write!(f, "解析公钥失败: {}", e),
+            TicketError::InvalidPem(msg) => write!(f, "解析 PEM 公钥失败: {}", msg),
+            TicketError::BadPrefix => write!(f, "票据格式无效: 缺少前缀"),
+            TicketError::EmptyTicket => write!(f, "票据为空"),
+            TicketError::MissingSeparator => write!(f, "票据格式无效: 缺少签名分隔符"),
+            TicketError::MalformedStructure => write!(f, "票据格式无效: 结构错误"),
+            TicketError::PayloadTooLarge => write!(f, "票据载荷过大"),
+            TicketError::Base64(e) => write!(f, "base64url 解码失败: {}", e),
+            TicketError::BadSignatureLength(len) => {
+                write!(f, "签名长度无效: 期望 64 字节, 实际 {} 字节", len)
+            }
+            TicketError::SignatureInvalid => write!(f, "签名验证失败"),
+            TicketError::PayloadParse(e) => write!(f, "解析载荷失败: {}", e),
+            TicketError::PayloadCbor(e) => write!(f, "解析载荷失败: {}", e),
+            TicketError::Expired => write!(f, "票据已过期"),
+            TicketError::DeviceMismatch { expected, actual } => {
+                write!(f, "目标设备 ID 不匹配: 期望 {}, 实际 {}", expected, actual)
+            }
+            TicketError::UnknownKeyId(kid) => write!(f, "未知的公钥 ID: {}", kid),
+            TicketError::ReplayDetected => write!(f, "票据已被使用"),
+            TicketError::ConfigurationError(msg) => write!(f, "票据验证配置无效: {}", msg),
+            TicketError::NotYetValid => write!(f, "票据尚未生效"),
+            TicketError::IssuedInFuture => write!(f, "票据签发时间晚于当前时间"),
+            TicketError::LifetimeTooLong => write!(f, "票据有效期过长"),
+            TicketError::ExpTooFar => write!(f, "票据过期时间距当前时间过远"),
+            TicketError::ClockError => write!(f, "系统时钟异常"),
+            TicketError::AudienceMismatch => write!(f, "票据受众不匹配"),
+            TicketError::Revoked => write!(f, "票据已被吊销"),
+            TicketError::WeakNonce => write!(f, "票据 nonce 过短"),
+            TicketError::KeyFetch(msg) => write!(f, "获取票据公钥失败: {}", msg),
+            TicketError::AlreadyUsed => write!(f, "一次性票据已被使用"),
+            TicketError::Decompress(e) => write!(f, "载荷解压失败: {}", e),
+            TicketError::ChannelBindingMismatch => write!(f, "通道绑定值不匹配"),
+            TicketError::IssuedTooFarFromNow => write!(f, "签发时间超出允许的窗口"),
+            TicketError::IssuedBeforeCutoff => write!(f, "票据签发于失效截止时间之前"),
+            TicketError::TimestampsInconsistent => write!(f, "票据过期时间早于签发时间"),
+            TicketError::Io(e) => write!(f, "读取票据失败: {}", e),
+            TicketError::WrongTicketType => write!(f, "票据类型不匹配"),
+            TicketError::RateLimited => write!(f, "验证失败次数过多, 请稍后重试"),
+            TicketError::AddressNotAllowed => write!(f, "对端地址不在票据允许的网络内"),
+            TicketError::DuplicateKey => write!(f, "公钥已加载, 不能重复添加"),
+            TicketError::DelegationInvalid => write!(f, "委托凭据无效"),
+            TicketError::DelegationExpired => write!(f, "委托的中间公钥已过期"),
+            TicketError::KeyIdConflict(kid) => write!(f, "公钥 ID {} 已对应另一个公钥", kid),
+            TicketError::MalformedDeviceId => write!(f, "票据中的设备 ID 为空或格式不合法"),
+            TicketError::UnknownField(field) => write!(f, "载荷包含未知字段: {}", field),
+            TicketError::MissingRequiredClaim(name) => write!(f, "票据缺少必需的声明: {}", name),
+        }
+    }
+}
+
+impl TicketError {
+    /// 稳定的错误代码, 供界面按代码查找本地化文案
+    ///
+    /// 代码一经发布不再修改或复用, 新增错误类型只会新增代码; 同一类原因共用一个代码
+    /// (如各种公钥解析失败都是 `KEY_INVALID`)。当前全部代码:
+    ///
+    /// | 代码 | 含义 |
+    /// |------|------|
+    /// | `NO_KEY` | 未设置公钥 |
+    /// | `KEY_INVALID` | 公钥无效 |
+    /// | `DUPLICATE_KEY` | 重复添加同一公钥 |
+    /// | `KEY_ID_CONFLICT` | 同一公钥 ID 对应不同公钥 |
+    /// | `KEY_FETCH_FAILED` | 获取公钥失败 |
+    /// | `UNKNOWN_KID` | 票据指定的公钥未加载 |
+    /// | `BAD_PREFIX` | 缺少票据前缀 |
+    /// | `EMPTY_TICKET` | 票据为空 |
+    /// | `MALFORMED` | 票据结构或编码无效 |
+    /// | `TOO_LARGE` | 载荷过大 |
+    /// | `BAD_PAYLOAD` | 载荷无法解析 |
+    /// | `SIG_INVALID` | 签名无效 |
+    /// | `DELEGATION_INVALID` | 委托凭据无效 |
+    /// | `DELEGATION_EXPIRED` | 委托的中间公钥已过期 |
+    /// | `EXPIRED` | 已过期 |
+    /// | `NOT_YET_VALID` | 尚未生效 |
+    /// | `ISSUED_IN_FUTURE` | 签发时间晚于当前时间 |
+    /// | `IAT_OUT_OF_WINDOW` | 签发时间超出允许的窗口 |
+    /// | `ISSUED_BEFORE_CUTOFF` | 票据签发于失效截止时间之前 |
+    /// | `LIFETIME_TOO_LONG` | 有效期过长 |
+    /// | `EXP_TOO_FAR` | 过期时间距当前时间过远 |
+    /// | `TIMESTAMPS_INCONSISTENT` | 过期时间早于签发时间 |
+    /// | `CLOCK_ERROR` | 本机时钟异常 |
+    /// | `MALFORMED_DEVICE_ID` | 设备 ID 为空或格式不合法 |
+    /// | `UNKNOWN_FIELD` | 载荷包含未知字段 |
+    /// | `MISSING_CLAIM` | 缺少必需的声明 |
+    /// | `DEVICE_MISMATCH` | 目标设备不匹配 |
+    /// | `AUDIENCE_MISMATCH` | 受众不匹配 |
+    /// | `WRONG_TYPE` | 票据类型不匹配 |
+    /// | `CHANNEL_BINDING_MISMATCH` | 通道绑定不匹配 |
+    /// | `ADDRESS_NOT_ALLOWED` | 对端地址不在允许的网络内 |
+    /// | `WEAK_NONCE` | nonce 过短 |
+    /// | `REPLAYED` | 票据已被使用 |
+    /// | `ALREADY_USED` | 一次性票据已被使用 |
+    /// | `REVOKED` | 已被吊销 |
+    /// | `RATE_LIMITED` | 失败次数过多, 冷却中 |
+    /// | `CONFIG` | 验证器配置无效 |
+    /// | `IO_ERROR` | 读取票据输入失败 |
+    pub fn code(&self) -> &'static str {
+        match self {
+            TicketError::MissingPublicKey => "NO_KEY",
+            TicketError::KeyHex(_)
+            | TicketError::BadKeyLength(_)
+            | TicketError::InvalidKey(_)
+            | TicketError::InvalidPem(_) => "KEY_INVALID",
+            TicketError::KeyFetch(_) => "KEY_FETCH_FAILED",
+            TicketError::DuplicateKey => "DUPLICATE_KEY",
+            TicketError::KeyIdConflict(_) => "KEY_ID_CONFLICT",
+            TicketError::UnknownKeyId(_) => "UNKNOWN_KID",
+            TicketError::BadPrefix => "BAD_PREFIX",
+            TicketError::EmptyTicket => "EMPTY_TICKET",
+            TicketError::MissingSeparator
+            | TicketError::MalformedStructure
+            | TicketError::Base64(_) => "MALFORMED",
+            TicketError::PayloadTooLarge => "TOO_LARGE",
+            TicketError::PayloadParse(_)
+            | TicketError::PayloadCbor(_)
+            | TicketError::Decompress(_) => "BAD_PAYLOAD",
+            TicketError::BadSignatureLength(_) | TicketError::SignatureInvalid => "SIG_INVALID",
+            TicketError::DelegationInvalid => "DELEGATION_INVALID",
+            TicketError::DelegationExpired => "DELEGATION_EXPIRED",
+            TicketError::Expired => "EXPIRED",
+            TicketError::NotYetValid => "NOT_YET_VALID",
+            TicketError::IssuedInFuture => "ISSUED_IN_FUTURE",
+            TicketError::IssuedTooFarFromNow => "IAT_OUT_OF_WINDOW",
+            TicketError::IssuedBeforeCutoff => "ISSUED_BEFORE_CUTOFF",
+            TicketError::LifetimeTooLong => "LIFETIME_TOO_LONG",
+            TicketError::ExpTooFar => "EXP_TOO_FAR",
+            TicketError::TimestampsInconsistent => "TIMESTAMPS_INCONSISTENT",
+            TicketError::ClockError => "CLOCK_ERROR",
+            TicketError::MalformedDeviceId => "MALFORMED_DEVICE_ID",
+            TicketError::UnknownField(_) => "UNKNOWN_FIELD",
+            TicketError::MissingRequiredClaim(_) => "MISSING_CLAIM",
+            TicketError::DeviceMismatch { .. } => "DEVICE_MISMATCH",
+            TicketError::AudienceMismatch => "AUDIENCE_MISMATCH",
+            TicketError::WrongTicketType => "WRONG_TYPE",
+            TicketError::ChannelBindingMismatch => "CHANNEL_BINDING_MISMATCH",
+            TicketError::AddressNotAllowed => "ADDRESS_NOT_ALLOWED",
+            TicketError::WeakNonce => "WEAK_NONCE",
+            TicketError::ReplayDetected => "REPLAYED",
+            TicketError::AlreadyUsed => "ALREADY_USED",
+            TicketError::Revoked => "REVOKED",
+            TicketError::RateLimited => "RATE_LIMITED",
+            TicketError::ConfigurationError(_) => "CONFIG",
+            TicketError::Io(_) => "IO_ERROR",
+        }
+    }
+
+    /// 验证失败时的日志级别: 结构性拒绝 (前缀、分段、编码错误) 多为误判为票据的普通输入,
+    /// 记为 debug, 其余认证失败记为 warn
+    fn log_level(&self) -> log::Level {
+        match self {
+            TicketError::BadPrefix
+            | TicketError::EmptyTicket
+            | TicketError::MissingSeparator
+            | TicketError::MalformedStructure
+            | TicketError::Base64(_)
+            | TicketError::BadSignatureLength(_) => log::Level::Debug,
+            _ => log::Level::Warn,
+        }
+    }
+}
+
+impl std::error::Error for TicketError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TicketError::KeyHex(e) => Some(e),
+            TicketError::InvalidKey(e) => Some(e),
+            TicketError::Base64(e) => Some(e),
+            TicketError::PayloadParse(e) => Some(e),
+            TicketError::PayloadCbor(e) => Some(e),
+            TicketError::Decompress(e) => Some(e),
+            TicketError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<base64::DecodeError> for TicketError {
+    fn from(e: base64::DecodeError) -> Self {
+        TicketError::Base64(e)
+    }
+}
+
+impl From<serde_json::Error> for TicketError {
+    fn from(e: serde_json::Error) -> Self {
+        TicketError::PayloadParse(e)
+    }
+}
+
+/// 票据格式版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketVersion {
+    /// `TICKET:v1:`, JSON 载荷
+    V1,
+    /// `TICKET:v2:`, CBOR 载荷, 编码后更短
+    V2,
+}
+
+impl TicketVersion {
+    const ALL: [TicketVersion; 2] = [TicketVersion::V1, TicketVersion::V2];
+
+    /// 前缀末尾的版本标记
+    fn marker(self) -> &'static str {
+        match self {
+            TicketVersion::V1 => "v1:",
+            TicketVersion::V2 => "v2:",
+        }
+    }
+
+    /// 二进制帧 (见 [`encode_binary`]) 首字节的版本号
+    fn binary_tag(self) -> u8 {
+        match self {
+            TicketVersion::V1 => 1,
+            TicketVersion::V2 => 2,
+        }
+    }
+
+    fn decode_payload(self, payload_bytes: &[u8]) -> Result<TicketPayload, TicketError> {
+        match self {
+            TicketVersion::V1 => parse_json_payload(payload_bytes),
+            TicketVersion::V2 => {
+                ciborium::from_reader(payload_bytes).map_err(TicketError::PayloadCbor)
+            }
+        }
+    }
+
+    /// 解析载荷, 遇到未知字段 (包括嵌套对象中的) 时返回 [`TicketError::UnknownField`]
+    fn decode_payload_strict(self, payload_bytes: &[u8]) -> Result<TicketPayload, TicketError> {
+        let strict: StrictPayload = match self {
+            TicketVersion::V1 => serde_json::from_slice(payload_bytes)?,
+            TicketVersion::V2 => {
+                ciborium::from_reader(payload_bytes).map_err(TicketError::PayloadCbor)?
+            }
+        };
+        match strict.unknown_field {
+            Some(field) => Err(TicketError::UnknownField(field)),
+            None => Ok(strict.payload),
+        }
+    }
+}
+
+/// 记录反序列化时被忽略的第一个字段, 相当于 `#[serde(deny_unknown_fields)]`
+struct StrictPayload {
+    payload: TicketPayload,
+    unknown_field: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for StrictPayload {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut unknown_field = None;
+        let payload = serde_ignored::deserialize(deserializer, |path| {
+            unknown_field.get_or_insert_with(|| path.to_string());
+        })?;
+        Ok(Self {
+            payload,
+            unknown_field,
+        })
+    }
+}
+
+/// 载荷段和签名段的 base64 编码方式
+///
+/// 只影响传输编码: 签名始终覆盖解码后的载荷字节, 同一张票据换用任一编码方式
+/// 签名都不变。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Base64Variant {
+    /// URL 安全字母表 (`-_`), 无填充, 本项目签发方使用的格式
+    #[default]
+    UrlSafeNoPad,
+    /// URL 安全字母表 (`-_`), 带 `=` 填充
+    UrlSafe,
+    /// 标准字母表 (`+/`), 带 `=` 填充
+    Standard,
+    /// 标准字母表 (`+/`), 无填充
+    StandardNoPad,
+}
+
+impl Base64Variant {
+    fn engine(self) -> &'static GeneralPurpose {
+        match self {
+            Base64Variant::UrlSafeNoPad => &URL_SAFE_NO_PAD,
+            Base64Variant::UrlSafe => &URL_SAFE,
+            Base64Variant::Standard => &STANDARD,
+            Base64Variant::StandardNoPad => &STANDARD_NO_PAD,
+        }
+    }
+
+    fn decode(self, segment: &[u8]) -> Result<Vec<u8>, TicketError> {
+        Ok(self.engine().decode(segment)?)
+    }
+}
+
+/// 解析 v1 JSON 载荷
+#[cfg(not(feature = "simd-json"))]
+fn parse_json_payload(payload_bytes: &[u8]) -> Result<TicketPayload, TicketError> {
+    Ok(serde_json::from_slice(payload_bytes)?)
+}
+
+/// 解析 v1 JSON 载荷 (simd-json 后端)
+///
+/// 错误同样转换为 [`TicketError::PayloadParse`], 调用方看到的错误类型与默认后端一致。
+#[cfg(feature = "simd-json")]
+fn parse_json_payload(payload_bytes: &[u8]) -> Result<TicketPayload, TicketError> {
+    // simd-json 原地解析, 需要可写的副本
+    let mut bytes = payload_bytes.to_vec();
+    simd_json::serde::from_slice(&mut bytes)
+        .map_err(|e| TicketError::PayloadParse(serde::de::Error::custom(e)))
+}
+
+/// 时间源
+///
+/// 验证器的全部时间比较都通过该 trait 获取当前时间,
+/// 测试可以固定时间, 没有 `SystemTime` 的平台 (如浏览器) 可以自行提供实现。
+pub trait Clock: Send + Sync {
+    /// 当前 Unix 秒
+    fn now_unix(&self) -> i64;
+}
+
+/// 默认时间源, 读取系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> i64 {
+        unix_time(SystemTime::now())
+    }
+}
+
+/// 目标设备绑定方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceBinding {
+    /// 票据 dst_id 必须与给定设备 ID 一致
+    Exact(String),
+    /// 不校验 dst_id, 任何设备都接受 (仅限明确需要的场景)
+    AnyDevice,
+}
+
+/// 一次验证的结果摘要, 传给 [`TicketVerifier::with_on_result`] 设置的回调
+#[derive(Debug)]
+pub struct VerifyOutcome<'a> {
+    /// 失败原因, None 表示验证成功
+    pub error: Option<&'a TicketError>,
+    /// 发起方设备 ID (载荷可解码时提供; 验证失败时未经验签, 只能用于统计)
+    pub src_id: Option<&'a str>,
+    /// 目标设备 (同上)
+    pub dst_id: Option<&'a DstId>,
+    /// 验证耗时
+    pub elapsed: Duration,
+}
+
+impl VerifyOutcome<'_> {
+    /// 是否验证成功
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// 验证通过的票据
+#[derive(Debug, Clone)]
+pub struct VerifiedTicket {
+    /// 已验签的载荷
+    pub payload: TicketPayload,
+    /// 距离过期的剩余时间, 已过期 (处于时钟偏差容忍范围内) 时为 0
+    pub remaining: Duration,
+    /// 本次验证各阶段的耗时
+    pub timings: VerifyTimings,
+    /// 剩余时间少于 [`with_soft_expiry`](TicketVerifier::with_soft_expiry) 设置的阈值,
+    /// 连接层应在后台提前获取新票据
+    pub near_expiry: bool,
+    /// 票据已过期, 但仍在 [`with_expiry_grace`](TicketVerifier::with_expiry_grace) 设置的
+    /// 宽限期内; 由调用方决定是否完成进行中的连接
+    pub expired_within_grace: bool,
+    /// 验签公钥的 kid; 公钥未设置 kid 时为其指纹 (见 [`TicketVerifier::key_fingerprints`]),
+    /// 委托票据为中间公钥的指纹
+    ///
+    /// 用于在轮换公钥期间确认客户端确实使用新公钥, 或在怀疑某个公钥泄露时排查。
+    pub matched_kid: Option<String>,
+}
+
+/// 验证各阶段的耗时, 用于判断设备是否负载过高
+///
+/// 命中验签缓存时两项均为 0。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifyTimings {
+    /// 解码 base64、解析载荷 (及解压) 的耗时
+    pub parse: Duration,
+    /// 验证签名的耗时
+    pub signature: Duration,
+}
+
+/// 票据声称的时间信息, 见 [`ticket_time_info`]
+///
+/// **未经验签, 仅供界面展示**
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TicketTimeInfo {
+    /// 签发时间戳
+    pub iat: i64,
+    /// 过期时间戳
+    pub exp: i64,
+    /// 按本机时钟计算的剩余时间, 已过期时为 0
+    pub remaining: Duration,
+}
+
+/// 审计用的验证报告, 见 [`TicketVerifier::verify_audit`]
+#[derive(Debug)]
+pub struct VerifyReport {
+    /// 签名是否有效; 为 false 时下面针对声明的检查项都不可信, 一律为 false
+    pub signature_ok: bool,
+    /// 是否已过期 (仅在签名有效时判断)
+    pub expired: bool,
+    /// 目标设备是否匹配 (仅在签名有效时判断; 验证器配置为接受任意设备时为 true)
+    pub device_match: bool,
+    /// 解码出的声明; `signature_ok` 为 false 时未经验签, 只能用于展示
+    pub decoded_claims: Option<TicketPayload>,
+    /// 全部检查是否通过 (不包括防重放检查)
+    pub accepted: bool,
+    /// 第一个未通过的检查
+    pub first_error: Option<TicketError>,
+}
+
+/// 验证器配置摘要, 见 [`TicketVerifier::config_summary`]
+///
+/// 只包含公钥指纹 (SHA-256 前 8 字节), 不包含完整公钥, 可以直接写入日志。
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifierConfigSummary {
+    /// 接受的票据前缀 (不含版本标记)
+    pub prefixes: Vec<String>,
+    /// 是否接受不带版本标记的旧格式票据
+    pub legacy_unversioned: bool,
+    /// 已加载的公钥
+    pub keys: Vec<KeySummary>,
+    /// 委托票据的信任锚公钥指纹
+    pub trust_anchors: Vec<String>,
+    /// 允许的时钟偏差 (秒)
+    pub clock_skew: i64,
+    /// 票据最长有效期 (秒)
+    pub max_lifetime: Option<i64>,
+    /// exp 距当前时间的最大跨度 (秒)
+    pub max_absolute_exp: Option<i64>,
+    /// iat 与当前时间的最大偏离 (秒)
+    pub iat_window: Option<i64>,
+    /// 期望的受众
+    pub expected_audience: Option<String>,
+    /// 期望的票据类型
+    pub expected_type: Option<String>,
+    /// 设备绑定: `argument` (使用 verify 传入的设备 ID)、`any` 或 `exact:<设备 ID>`
+    pub device_binding: String,
+    /// nonce 最小长度
+    pub min_nonce_len: usize,
+    /// 载荷解码后的最大字节数
+    pub max_payload_bytes: usize,
+    /// 是否启用防重放缓存
+    pub replay_cache: bool,
+    /// 是否启用吊销列表
+    pub revocation_list: bool,
+    /// 是否启用验签缓存
+    pub verify_cache: bool,
+    /// 是否启用失败限流
+    pub rate_limiter: bool,
+}
+
+/// 单个公钥的摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct KeySummary {
+    /// 公钥 ID
+    pub kid: Option<String>,
+    /// 签名算法
+    pub algorithm: String,
+    /// 公钥指纹; 自定义算法的公钥无法取得原始字节, 为 None
+    pub fingerprint: Option<String>,
+    /// 停用时间戳
+    pub valid_until: Option<i64>,
+}
+
+impl std::fmt::Display for VerifierConfigSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn opt<T: std::fmt::Display>(value: &Option<T>) -> String {
+            value.as_ref().map_or_else(|| "-".to_owned(), ToString::to_string)
+        }
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|k| format!("{}:{}({})", opt(&k.kid), opt(&k.fingerprint), k.algorithm))
+            .collect();
+        write!(
+            f,
+            "prefixes=[{}] legacy_unversioned={} keys=[{}] trust_anchors=[{}] clock_skew={} \
+             max_lifetime={} max_absolute_exp={} iat_window={} aud={} typ={} device_binding={} \
+             min_nonce_len={} max_payload_bytes={} replay_cache={} revocation_list={} \
+             verify_cache={} rate_limiter={}",
+            self.prefixes.join(","),
+            self.legacy_unversioned,
+            keys.join(","),
+            self.trust_anchors.join(","),
+            self.clock_skew,
+            opt(&self.max_lifetime),
+            opt(&self.max_absolute_exp),
+            opt(&self.iat_window),
+            opt(&self.expected_audience),
+            opt(&self.expected_type),
+            self.device_binding,
+            self.min_nonce_len,
+            self.max_payload_bytes,
+            self.replay_cache,
+            self.revocation_list,
+            self.verify_cache,
+            self.rate_limiter
+        )
+    }
+}
+
+/// 公钥指纹: SHA-256 前 8 字节的十六进制, 用于在日志中区分公钥而不暴露完整内容
+fn key_fingerprint(key: &[u8]) -> String {
+    hex::encode(&Sha256::digest(key)[..8])
+}
+
+/// 验证结果回调
+type ResultHook = Arc<dyn Fn(&VerifyOutcome<'_>) + Send + Sync>;
+
+/// 单次验证的上下文: 本端观察到的连接信息 (用于检查绑定到连接的声明, 未观察到的项为 None),
+/// 以及需要时记录各阶段耗时的位置
+#[derive(Default)]
+struct VerifyContext<'a> {
+    /// 握手值, 见 [`channel_binding`]
+    channel_binding: Option<&'a [u8]>,
+    /// 对端 IP 地址
+    ip: Option<IpAddr>,
+    /// 验签及解析的耗时, 命中验签缓存时不更新
+    timings: Option<&'a Cell<VerifyTimings>>,
+    /// 接受过期未超过宽限期的票据
+    expiry_grace: bool,
+    /// 验证通过时记录验签公钥的标识
+    matched_key: Option<&'a Cell<Option<String>>>,
+    /// 未经验签解出的载荷, 供回调、日志和限流共用, 每次验证最多解码一次
+    unverified: OnceCell<Option<TicketPayload>>,
+}
+
+/// 已验签的载荷及验签公钥的标识, 验签缓存保存的即是该结构
+#[derive(Clone, Default)]
+struct DecodedTicket {
+    payload: TicketPayload,
+    /// 公钥的 kid, 未设置 kid 时为公钥指纹; 自定义算法公钥未设置 kid 时为 None
+    matched_key: Option<String>,
+}
+
+/// 验签时按哪个时刻判断公钥是否已停用、委托凭据是否已过期
+#[derive(Clone, Copy)]
+enum KeyEpoch {
+    /// 当前时间 (Unix 秒), 实时验证使用
+    Now(i64),
+    /// 票据载荷的 iat, 离线审计历史票据使用: 签发时仍在使用的公钥照常参与验签
+    IssuedAt,
+}
+
+impl KeyEpoch {
+    /// 载荷签发时间为 `iat` 时用于比较的时刻
+    fn at(self, iat: i64) -> i64 {
+        match self {
+            KeyEpoch::Now(now) => now,
+            KeyEpoch::IssuedAt => iat,
+        }
+    }
+}
+
+/// 已加载的验签公钥
+#[derive(Clone)]
+struct TicketKey {
+    /// 公钥 ID, 与票据载荷中的 kid 对应
+    kid: Option<String>,
+    scheme: Arc<dyn SignatureScheme>,
+    /// 停用时间戳 (Unix 秒), 晚于该时刻不再用于验签; None 表示长期有效
+    valid_until: Option<i64>,
+    /// Ed25519 公钥的原始字节, 用于检测重复加载; 自定义算法为 None
+    key_bytes: Option<[u8; 32]>,
+}
+
+impl TicketKey {
+    fn ed25519(kid: Option<String>, key: VerifyingKey) -> Self {
+        Self {
+            kid,
+            scheme: Arc::new(Ed25519Scheme::new(key)),
+            valid_until: None,
+            key_bytes: Some(key.to_bytes()),
+        }
+    }
+
+    fn is_active(&self, now: i64) -> bool {
+        self.valid_until.map_or(true, |until| now <= until)
+    }
+
+    /// 诊断信息中标识该公钥: kid, 未设置时为公钥指纹
+    fn label(&self) -> Option<String> {
+        self.kid
+            .clone()
+            .or_else(|| self.key_bytes.as_ref().map(|b| key_fingerprint(b)))
+    }
+}
+
+/// 验证器配置
+#[derive(Clone)]
+struct VerifierConfig {
+    /// 公钥列表 (密钥轮换期间可同时存在新旧公钥, 混合部署时可包含不同算法)
+    public_keys: Vec<TicketKey>,
+    /// 防重放缓存 (可选)
+    replay_cache: Option<Arc<dyn ReplayStore>>,
+    /// 验证失败限流 (可选)
+    rate_limiter: Option<RateLimiter>,
+    /// 允许的时钟偏差 (秒)
+    clock_skew: i64,
+    /// 票据最长有效期 (秒), None 表示不限制
+    max_lifetime: Option<i64>,
+    /// exp 距当前时间的最大跨度 (秒), None 表示不限制
+    max_absolute_exp: Option<i64>,
+    /// 剩余有效期低于该值 (秒) 时提示即将过期, None 表示不提示
+    soft_expiry: Option<i64>,
+    /// 过期后仍接受的宽限期 (秒), 只用于 verify_detailed
+    expiry_grace: i64,
+    /// 载荷包含未知字段时拒绝
+    deny_unknown_fields: bool,
+    /// 要求票据同时携带 nonce 和 kid
+    strict_claims: bool,
+    /// iat 早于该时间戳的票据一律拒绝, `i64::MIN` 表示不限制; 可在共享的验证器上实时更新
+    min_iat: Arc<AtomicI64>,
+    /// 时间源
+    clock: Arc<dyn Clock>,
+    /// 期望的受众, None 表示不检查
+    expected_audience: Option<String>,
+    /// 期望的票据类型, None 表示不检查
+    expected_type: Option<String>,
+    /// 吊销列表 (可选)
+    revocation_list: Option<RevocationList>,
+    /// 验签结果缓存 (可选)
+    verify_cache: Option<Arc<VerifyCache>>,
+    /// 载荷解码后的最大字节数
+    max_payload_bytes: usize,
+    /// 显式设置的设备绑定, None 表示使用 verify 传入的设备 ID
+    device_binding: Option<DeviceBinding>,
+    /// 验证结果回调 (可选)
+    on_result: Option<ResultHook>,
+    /// 接受的票据前缀中版本标记之前的部分, 默认只有 `TICKET:`
+    prefix_bases: Vec<String>,
+    /// 前缀后没有版本标记时按 v1 处理
+    legacy_unversioned: bool,
+    /// 载荷段和签名段的 base64 编码方式
+    base64_variant: Base64Variant,
+    /// nonce 最小长度 (字符数)
+    min_nonce_len: usize,
+    /// iat 与当前时间的最大偏离 (秒), None 表示不限制
+    iat_window: Option<i64>,
+    /// 追加已加载过的公钥时返回错误, 而不是静默忽略
+    reject_duplicate_keys: bool,
+    /// 委托票据的信任锚公钥
+    trust_anchors: Vec<VerifyingKey>,
+    /// src_id 和 dst_id 的格式要求
+    device_id_format: DeviceIdFormat,
 }
 
 /// 票据验证器
+///
+/// 配置保存在 `Arc` 中, `clone` 只复制引用计数, 可以跨线程共享 (`Send + Sync`)。
+/// 推荐在启动时配置好一个实例, 再把克隆出的句柄分发给各个工作线程;
+/// `verify` 只需要 `&self`, 除启用防重放缓存、吊销列表或验签缓存外不加锁。
+#[derive(Clone)]
 pub struct TicketVerifier {
-    /// Ed25519 公钥
-    public_key: Option<VerifyingKey>,
+    config: Arc<VerifierConfig>,
 }
 
 fn build_api_url(api_server: &str, path: &str) -> Option<String> {
@@ -65,27 +1298,27 @@ fn build_api_url(api_server: &str, path: &str) -> Option<String> {
 
 fn fetch_ticket_public_key(api_server: &str) -> Option<String> {
     let url = build_api_url(api_server, "/api/ticket/pubkey")?;
-    log::debug!("开始获取票据公钥: {}", url);
+    log::debug!(target: LOG_TARGET, "开始获取票据公钥: {}", url);
     let client = create_http_client_with_url(&url);
     let resp = client.get(&url).timeout(Duration::from_secs(5)).send();
     match resp {
         Ok(resp) => match HbbHttpResponse::<TicketPublicKeyResponse>::try_from(resp) {
             Ok(HbbHttpResponse::Data(data)) if !data.public_key.is_empty() => {
-                log::debug!("获取票据公钥成功: len={}", data.public_key.len());
+                log::debug!(target: LOG_TARGET, "获取票据公钥成功: len={}", data.public_key.len());
                 Some(data.public_key)
             }
             Ok(HbbHttpResponse::Error(err)) => {
-                log::warn!("获取票据公钥失败: {}", err);
+                log::warn!(target: LOG_TARGET, "获取票据公钥失败: {}", err);
                 None
             }
             Ok(_) => None,
             Err(err) => {
-                log::warn!("票据公钥响应解析失败: {}", err);
+                log::warn!(target: LOG_TARGET, "票据公钥响应解析失败: {}", err);
                 None
             }
         },
         Err(err) => {
-            log::warn!("票据公钥请求错误: {}", err);
+            log::warn!(target: LOG_TARGET, "票据公钥请求错误: {}", err);
             None
         }
     }
@@ -94,6 +1327,7 @@ fn fetch_ticket_public_key(api_server: &str) -> Option<String> {
 fn request_ticket(api_server: &str, access_token: &str, target_id: &str) -> Option<String> {
     let url = build_api_url(api_server, "/api/ticket")?;
     log::debug!(
+        target: LOG_TARGET,
         "开始请求票据: url={} target_id={} token_len={}",
         url,
         target_id,
@@ -112,6 +1346,7 @@ fn request_ticket(api_server: &str, access_token: &str, target_id: &str) -> Opti
         Ok(resp) => match HbbHttpResponse::<TicketResponse>::try_from(resp) {
             Ok(HbbHttpResponse::Data(data)) if !data.ticket.is_empty() => {
                 log::debug!(
+                    target: LOG_TARGET,
                     "获取票据成功: len={} expires_in={}",
                     data.ticket.len(),
                     data.expires_in
@@ -119,17 +1354,17 @@ fn request_ticket(api_server: &str, access_token: &str, target_id: &str) -> Opti
                 Some(data.ticket)
             }
             Ok(HbbHttpResponse::Error(err)) => {
-                log::warn!("获取票据失败: {}", err);
+                log::warn!(target: LOG_TARGET, "获取票据失败: {}", err);
                 None
             }
             Ok(_) => None,
             Err(err) => {
-                log::warn!("票据响应解析失败: {}", err);
+                log::warn!(target: LOG_TARGET, "票据响应解析失败: {}", err);
                 None
             }
         },
         Err(err) => {
-            log::warn!("票据请求错误: {}", err);
+            log::warn!(target: LOG_TARGET, "票据请求错误: {}", err);
             None
         }
     }
@@ -138,7 +1373,7 @@ fn request_ticket(api_server: &str, access_token: &str, target_id: &str) -> Opti
 fn get_cached_public_key() -> String {
     let key = crate::get_builtin_option(TICKET_PUBLIC_KEY_OPTION);
     if !key.is_empty() {
-        log::debug!("票据公钥使用内置配置");
+        log::debug!(target: LOG_TARGET, "票据公钥使用内置配置");
         return key;
     }
     LocalConfig::get_option(TICKET_PUBLIC_KEY_OPTION)
@@ -147,19 +1382,19 @@ fn get_cached_public_key() -> String {
 pub fn get_ticket_public_key() -> String {
     let cached = get_cached_public_key();
     if !cached.is_empty() {
-        log::debug!("票据公钥使用本地缓存");
+        log::debug!(target: LOG_TARGET, "票据公钥使用本地缓存");
         return cached;
     }
     let api_server = Config::get_option(keys::OPTION_API_SERVER);
     if api_server.is_empty() {
-        log::debug!("票据公钥获取跳过: 未配置 api-server");
+        log::debug!(target: LOG_TARGET, "票据公钥获取跳过: 未配置 api-server");
         return String::new();
     }
     if let Some(key) = fetch_ticket_public_key(&api_server) {
         if !key.is_empty() {
             LocalConfig::set_option(TICKET_PUBLIC_KEY_OPTION.to_owned(), key.clone());
         }
-        log::debug!("票据公钥从 API 获取完成");
+        log::debug!(target: LOG_TARGET, "票据公钥从 API 获取完成");
         return key;
     }
     String::new()
@@ -167,174 +1402,4625 @@ pub fn get_ticket_public_key() -> String {
 
 pub fn try_request_ticket(target_id: &str) -> Option<String> {
     if target_id.is_empty() {
-        log::debug!("票据请求跳过: target_id 为空");
+        log::debug!(target: LOG_TARGET, "票据请求跳过: target_id 为空");
         return None;
     }
     let api_server = Config::get_option(keys::OPTION_API_SERVER);
     if api_server.is_empty() {
-        log::debug!("票据请求跳过: 未配置 api-server");
+        log::debug!(target: LOG_TARGET, "票据请求跳过: 未配置 api-server");
         return None;
     }
     let access_token = LocalConfig::get_option("access_token");
     if access_token.is_empty() {
-        log::debug!("票据请求跳过: access_token 为空");
+        log::debug!(target: LOG_TARGET, "票据请求跳过: access_token 为空");
         return None;
     }
     let target_id = target_id.split('@').next().unwrap_or(target_id);
-    log::debug!("票据请求准备完成: target_id={}", target_id);
+    log::debug!(target: LOG_TARGET, "票据请求准备完成: target_id={}", target_id);
     request_ticket(&api_server, &access_token, target_id)
 }
 
 impl TicketVerifier {
     /// 创建新的票据验证器
     pub fn new() -> Self {
-        Self { public_key: None }
+        Self {
+            config: Arc::new(VerifierConfig {
+                public_keys: Vec::new(),
+                replay_cache: None,
+                rate_limiter: None,
+                clock_skew: DEFAULT_CLOCK_SKEW_SECS,
+                max_lifetime: None,
+                max_absolute_exp: None,
+                soft_expiry: None,
+                expiry_grace: 0,
+                min_iat: Arc::new(AtomicI64::new(i64::MIN)),
+                deny_unknown_fields: false,
+                strict_claims: false,
+                clock: Arc::new(SystemClock),
+                expected_audience: None,
+                expected_type: None,
+                revocation_list: None,
+                verify_cache: None,
+                max_payload_bytes: DEFAULT_MAX_PAYLOAD_BYTES,
+                device_binding: None,
+                on_result: None,
+                prefix_bases: vec![DEFAULT_PREFIX_BASE.to_owned()],
+                legacy_unversioned: false,
+                base64_variant: Base64Variant::UrlSafeNoPad,
+                min_nonce_len: DEFAULT_MIN_NONCE_LEN,
+                iat_window: None,
+                reject_duplicate_keys: false,
+                trust_anchors: Vec::new(),
+                device_id_format: DeviceIdFormat::default(),
+            }),
+        }
     }
 
-    /// 使用十六进制字符串设置公钥
-    /// 公钥应从 API Server 获取并内置到客户端
-    pub fn set_public_key_hex(&mut self, hex_key: &str) -> Result<(), String> {
-        let key_bytes = hex::decode(hex_key)
-            .map_err(|e| format!("解码公钥失败: {}", e))?;
-        
-        if key_bytes.len() != 32 {
-            return Err(format!("公钥长度无效: 期望 32 字节, 实际 {} 字节", key_bytes.len()));
-        }
+    /// 创建构建器, 以链式调用声明配置
+    pub fn builder() -> TicketVerifierBuilder {
+        TicketVerifierBuilder::new()
+    }
 
-        let mut key_array = [0u8; 32];
-        key_array.copy_from_slice(&key_bytes);
+    /// 取得可修改的配置
+    ///
+    /// 配置被其他句柄共享时先复制一份 (写时复制), 不影响已经分发出去的句柄;
+    /// 复制出的配置按当前值另建 iat 截止时间, 此后与原句柄各自独立。
+    /// 配置变化后已缓存的验签结果不再可信, 这里同时换用新的空缓存。
+    fn config_mut(&mut self) -> &mut VerifierConfig {
+        let forked = Arc::get_mut(&mut self.config).is_none();
+        let config = Arc::make_mut(&mut self.config);
+        if forked {
+            let min_iat = config.min_iat.load(Ordering::Relaxed);
+            config.min_iat = Arc::new(AtomicI64::new(min_iat));
+        }
+        if let Some(cache) = config.verify_cache.as_mut() {
+            *cache = Arc::new(VerifyCache::new(cache.capacity()));
+        }
+        config
+    }
 
-        let verifying_key = VerifyingKey::from_bytes(&key_array)
-            .map_err(|e| format!("解析公钥失败: {}", e))?;
-        
-        self.public_key = Some(verifying_key);
+    /// 设置票据前缀, 默认 `TICKET:v1:`
+    ///
+    /// 用于私有部署按租户区分票据: 使用其他前缀签发的票据不会被识别为票据。
+    /// 前缀必须以版本标记 (`v1:` 或 `v2:`) 结尾, 验证器据此同时接受该命名空间下的
+    /// v1 与 v2 票据, 例如设置 `ACME:v1:` 后接受 `ACME:v1:` 和 `ACME:v2:`。
+    ///
+    /// 替换之前设置的全部前缀; 迁移期间需要同时接受多个前缀时再调用
+    /// [`add_prefix`](Self::add_prefix)。
+    pub fn with_prefix(&mut self, prefix: &str) -> Result<(), TicketError> {
+        let base = prefix_base(prefix)?;
+        self.config_mut().prefix_bases = vec![base.to_owned()];
         Ok(())
     }
 
-    /// 验证票据
-    /// 
-    /// # 参数
-    /// - `ticket`: 票据字符串 (格式: TICKET:v1:<base64url(payload)>.<base64url(signature)>)
-    /// - `my_device_id`: 本机设备 ID (用于验证 dst_id)
-    /// 
-    /// # 返回
-    /// - `Ok(TicketPayload)`: 验证成功，返回载荷
-    /// - `Err(String)`: 验证失败，返回错误信息
-    pub fn verify(&self, ticket: &str, my_device_id: &str) -> Result<TicketPayload, String> {
-        // 检查公钥是否已设置
-        let public_key = self.public_key.as_ref()
-            .ok_or_else(|| "公钥未设置".to_string())?;
-
-        // 检查票据格式
-        if !ticket.starts_with(TICKET_PREFIX) {
-            return Err("票据格式无效: 缺少前缀".to_string());
+    /// 追加一个接受的票据前缀, 用于迁移期间新旧前缀并存
+    ///
+    /// 格式要求与 [`with_prefix`](Self::with_prefix) 相同; 每张票据按匹配到的版本标记
+    /// 选择解码方式, 与具体前缀无关。
+    pub fn add_prefix(&mut self, prefix: &str) -> Result<(), TicketError> {
+        let base = prefix_base(prefix)?;
+        if !self.config.prefix_bases.iter().any(|b| b == base) {
+            self.config_mut().prefix_bases.push(base.to_owned());
         }
+        Ok(())
+    }
 
-        let content = &ticket[TICKET_PREFIX.len()..];
-        
-        // 分割载荷和签名
-        let dot_pos = content.rfind('.')
-            .ok_or_else(|| "票据格式无效: 缺少签名分隔符".to_string())?;
-        
-        let payload_b64 = &content[..dot_pos];
-        let signature_b64 = &content[dot_pos + 1..];
-
-        // 解码载荷
-        let payload_bytes = URL_SAFE_NO_PAD.decode(payload_b64)
-            .map_err(|e| format!("解码载荷失败: {}", e))?;
+    /// 检查密码是否为该验证器任一前缀下的票据
+    pub fn is_ticket(&self, password: &[u8]) -> bool {
+        std::str::from_utf8(password)
+            .map(|s| {
+                let ticket = self.versioned(s.as_bytes());
+                split_version(&ticket, &self.config.prefix_bases).is_some()
+            })
+            .unwrap_or(false)
+    }
 
-        // 解码签名
-        let signature_bytes = URL_SAFE_NO_PAD.decode(signature_b64)
-            .map_err(|e| format!("解码签名失败: {}", e))?;
+    /// 显式设置目标设备绑定, 优先于 verify 传入的设备 ID
+    ///
+    /// 未设置时 verify 总是要求 dst_id 与传入的设备 ID 一致, 传入空字符串不会跳过检查;
+    /// 确实需要接受任意设备时必须显式设置 [`DeviceBinding::AnyDevice`]。
+    pub fn with_device_binding(&mut self, binding: DeviceBinding) {
+        self.config_mut().device_binding = Some(binding);
+    }
 
-        if signature_bytes.len() != 64 {
-            return Err(format!("签名长度无效: 期望 64 字节, 实际 {} 字节", signature_bytes.len()));
+    /// 设置 nonce 最小长度 (字符数), 默认 16
+    ///
+    /// 签发方使用固定或过短的 nonce 时防重放缓存形同虚设, 严格的部署可以要求更长的 nonce。
+    pub fn with_min_nonce_len(&mut self, n: usize) -> Result<(), TicketError> {
+        if n == 0 {
+            return Err(TicketError::ConfigurationError(
+                "nonce 最小长度必须大于 0".to_owned(),
+            ));
         }
+        self.config_mut().min_nonce_len = n;
+        Ok(())
+    }
+
+    /// 设置载荷解码后的最大字节数, 默认 8 KiB
+    ///
+    /// 票据来自连接密码, 由对端任意构造; 超长载荷在 base64 解码前即被拒绝。
+    pub fn with_max_payload_bytes(&mut self, n: usize) -> Result<(), TicketError> {
+        if n == 0 {
+            return Err(TicketError::ConfigurationError(
+                "载荷大小上限必须大于 0".to_owned(),
+            ));
+        }
+        self.config_mut().max_payload_bytes = n;
+        Ok(())
+    }
+
+    /// 要求每张票据都携带 nonce (用于防重放) 和 kid (明确指定验签公钥), 默认关闭
+    ///
+    /// 缺少任一声明时返回 [`TicketError::MissingRequiredClaim`]; 关闭时缺少 kid 的票据
+    /// 依次尝试每个公钥, 与旧签发方兼容。
+    pub fn with_strict_claims(&mut self, enabled: bool) {
+        self.config_mut().strict_claims = enabled;
+    }
+
+    /// 拒绝包含未知字段的载荷, 默认关闭
+    ///
+    /// 默认忽略未知字段, 新版签发方增加的字段不影响旧客户端; 严格模式下签发方的
+    /// 字段名拼写错误 (如把 `exp` 写成 `exipry`) 会以 [`TicketError::UnknownField`]
+    /// 暴露出来, 适合在测试环境中发现签发方的回归问题。
+    pub fn with_deny_unknown_fields(&mut self, enabled: bool) {
+        self.config_mut().deny_unknown_fields = enabled;
+    }
+
+    /// 设置载荷段和签名段的 base64 编码方式, 默认 [`Base64Variant::UrlSafeNoPad`]
+    ///
+    /// 用于对接输出其它 base64 格式的签发方 (例如使用标准字母表和填充的 Java 实现)。
+    /// 签名始终覆盖解码后的载荷字节, 与传输编码无关。
+    pub fn with_base64_variant(&mut self, variant: Base64Variant) {
+        self.config_mut().base64_variant = variant;
+    }
+
+    /// 启用验签结果缓存, 最多缓存 `capacity` 张票据, 0 表示关闭
+    ///
+    /// 缓存只跳过解码和验签, 有效期等检查每次仍会执行;
+    /// 启用防重放缓存或吊销列表时不使用该缓存。
+    pub fn with_verify_cache(&mut self, capacity: usize) {
+        self.config_mut().verify_cache = if capacity > 0 {
+            Some(Arc::new(VerifyCache::new(capacity)))
+        } else {
+            None
+        };
+    }
+
+    /// 启用吊销列表, 列表可在验证器使用期间通过克隆的句柄实时更新
+    pub fn with_revocation_list(&mut self, list: RevocationList) {
+        self.config_mut().revocation_list = Some(list);
+    }
+
+    /// 设置期望的受众
+    ///
+    /// 设置后票据的 aud 必须包含该受众 (单个受众时须相同), 缺少 aud 的票据同样被拒绝;
+    /// 未设置时忽略 aud 字段。
+    pub fn with_expected_audience(&mut self, aud: &str) {
+        self.config_mut().expected_audience = Some(aud.to_owned());
+    }
+
+    /// 设置期望的票据类型
+    ///
+    /// 设置后票据必须携带相同的 typ, 缺少 typ 的票据同样被拒绝;
+    /// 未设置时忽略 typ 字段。
+    pub fn with_expected_type(&mut self, typ: &str) {
+        self.config_mut().expected_type = Some(typ.to_owned());
+    }
+
+    /// 替换时间源, 默认使用系统时钟
+    pub fn with_clock(&mut self, clock: Box<dyn Clock>) {
+        self.config_mut().clock = Arc::from(clock);
+    }
+
+    /// 设置允许的时钟偏差 (秒), 默认 30 秒
+    ///
+    /// 设备时钟不准时可以适当放宽, 但偏差越大, 过期票据仍可使用 (可被重放) 的窗口也越大。
+    pub fn with_clock_skew(&mut self, seconds: i64) -> Result<(), TicketError> {
+        if seconds < 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "时钟偏差不能为负数: {}",
+                seconds
+            )));
+        }
+        self.config_mut().clock_skew = seconds;
+        Ok(())
+    }
+
+    /// 设置票据最长有效期 (exp - iat, 秒), 默认不限制
+    ///
+    /// 免密票据等同于临时凭据, 建议设置为 600 秒左右,
+    /// 防止签发方异常签出长期有效的票据。
+    pub fn with_max_lifetime(&mut self, seconds: i64) -> Result<(), TicketError> {
+        if seconds <= 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "最长有效期必须为正数: {}",
+                seconds
+            )));
+        }
+        self.config_mut().max_lifetime = Some(seconds);
+        Ok(())
+    }
+
+    /// 设置票据中 src_id 和 dst_id 的格式要求
+    ///
+    /// 两者都必须非空、不超过 `max_len` 个字符, 且只含 ASCII 字母、数字和 `symbols` 中的符号;
+    /// dst_id 各项末尾的通配符 `*` 不计入。默认最长 64 个字符, 允许 `-_.@`。
+    /// 不符合时返回 [`TicketError::MalformedDeviceId`]。
+    pub fn with_device_id_format(
+        &mut self,
+        max_len: usize,
+        symbols: &str,
+    ) -> Result<(), TicketError> {
+        if max_len == 0 {
+            return Err(TicketError::ConfigurationError(
+                "设备 ID 最大长度必须为正数".to_owned(),
+            ));
+        }
+        if symbols.contains('*') || !symbols.is_ascii() {
+            return Err(TicketError::ConfigurationError(format!(
+                "设备 ID 允许的符号无效: {}",
+                symbols
+            )));
+        }
+        self.config_mut().device_id_format = DeviceIdFormat {
+            max_len,
+            symbols: symbols.to_owned(),
+        };
+        Ok(())
+    }
+
+    /// 接受前缀后不带版本标记的旧格式票据 (例如 `TICKET:<载荷>.<签名>`), 按 v1 JSON 格式解析;
+    /// 默认关闭
+    ///
+    /// **临时兼容措施**: 仅用于过渡期接受升级前的签发方签出的票据, 待其全部升级后应关闭,
+    /// 后续版本可能移除该选项。带版本标记的票据不受影响。
+    pub fn with_legacy_unversioned(&mut self, enabled: bool) {
+        self.config_mut().legacy_unversioned = enabled;
+    }
+
+    /// 旧格式票据补上 v1 版本标记, 其他输入原样返回
+    ///
+    /// 签名只覆盖载荷, 改写前缀不影响验签。
+    fn versioned<'a>(&self, ticket: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.config.legacy_unversioned
+            || split_version(ticket, &self.config.prefix_bases).is_some()
+        {
+            return Cow::Borrowed(ticket);
+        }
+        self.config
+            .prefix_bases
+            .iter()
+            .find_map(|base| {
+                let rest = ticket.strip_prefix(base.as_bytes())?;
+                let marker = TicketVersion::V1.marker().as_bytes();
+                Some(Cow::Owned([base.as_bytes(), marker, rest].concat()))
+            })
+            .unwrap_or(Cow::Borrowed(ticket))
+    }
+
+    /// 拒绝 exp 晚于当前时间 `seconds` 秒以上的票据, 默认不限制
+    ///
+    /// 与 [`with_max_lifetime`](Self::with_max_lifetime) 互补: 后者只比较 exp 与 iat,
+    /// 签发方时钟或配置出错时 (例如 exp 为 9999 年、iat 也异常) 仍可能放过,
+    /// 这里直接以本机时间为基准。超出时返回 [`TicketError::ExpTooFar`]。
+    pub fn with_max_absolute_exp(&mut self, seconds: i64) -> Result<(), TicketError> {
+        if seconds <= 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "过期时间跨度必须为正数: {}",
+                seconds
+            )));
+        }
+        self.config_mut().max_absolute_exp = Some(seconds);
+        Ok(())
+    }
+
+    /// 剩余有效期少于 `seconds` 秒时, [`verify_detailed`](Self::verify_detailed) 返回的
+    /// [`VerifiedTicket::near_expiry`] 为 true, 验证本身照常通过; 默认不提示
+    ///
+    /// 连接层据此在票据过期前于后台换取新票据, 避免会话中途因票据过期而重连。
+    pub fn with_soft_expiry(&mut self, seconds: i64) -> Result<(), TicketError> {
+        if seconds < 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "即将过期阈值不能为负数: {}",
+                seconds
+            )));
+        }
+        self.config_mut().soft_expiry = Some(seconds);
+        Ok(())
+    }
+
+    /// 票据过期 (超出时钟偏差) 后 `seconds` 秒内, [`verify_detailed`](Self::verify_detailed)
+    /// 仍返回成功并设置 [`VerifiedTicket::expired_within_grace`]; 默认 0, 不设宽限期
+    ///
+    /// 与时钟偏差不同, 宽限期用于容忍握手进行中才过期的票据, 而不是两端的时钟差异:
+    /// 其它验证方法不受影响, 超出宽限期仍返回 [`TicketError::Expired`]。
+    pub fn with_expiry_grace(&mut self, seconds: i64) -> Result<(), TicketError> {
+        if seconds < 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "过期宽限期不能为负数: {}",
+                seconds
+            )));
+        }
+        self.config_mut().expiry_grace = seconds;
+        Ok(())
+    }
+
+    /// 拒绝 iat 早于 `timestamp` (Unix 秒) 的全部票据, 无论是否过期, 默认不限制
+    ///
+    /// 用于安全事件后要求全部票据重新签发, 不必等待自然过期, 也不必逐张吊销;
+    /// 超出时返回 [`TicketError::IssuedBeforeCutoff`]。只需 `&self`, 可以在多个连接
+    /// 共享的验证器上随时调整: 克隆出的句柄共享该截止时间, 直到某个句柄修改其它配置
+    /// (写时复制) 后按当时的值分离。传入 `i64::MIN` 取消限制。
+    pub fn set_min_iat(&self, timestamp: i64) {
+        self.config.min_iat.store(timestamp, Ordering::Relaxed);
+    }
+
+    /// 要求票据的 iat 与当前时间相差不超过 `seconds` 秒 (双向), 默认不限制
+    ///
+    /// 适用于时钟同步良好的设备群, 例如拒绝签发于 120 秒之前的票据。
+    /// 该窗口本身就是容差, 不再叠加时钟偏差; 设置后取代 iat 晚于当前时间的检查
+    /// ([`TicketError::IssuedInFuture`]), 超出窗口时返回 [`TicketError::IssuedTooFarFromNow`]。
+    /// 过期和生效时间仍先按时钟偏差检查, 已过期的票据报告 [`TicketError::Expired`]。
+    pub fn with_iat_window(&mut self, seconds: i64) -> Result<(), TicketError> {
+        if seconds < 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "签发时间窗口不能为负数: {}",
+                seconds
+            )));
+        }
+        self.config_mut().iat_window = Some(seconds);
+        Ok(())
+    }
+
+    /// 设置验证结果回调, 每次验证结束 (包括前缀不匹配等提前拒绝) 都会调用一次
+    ///
+    /// 用于把成功率、失败原因和耗时接入监控系统。回调中的 panic 会被捕获并记录,
+    /// 不影响验证结果 (以 `panic = "abort"` 编译时无法捕获, 回调本身不应 panic)。
+    pub fn with_on_result(&mut self, hook: Box<dyn Fn(&VerifyOutcome<'_>) + Send + Sync>) {
+        self.config_mut().on_result = Some(Arc::from(hook));
+    }
+
+    /// 启用防重放缓存, 同一 nonce 在票据有效期内只能通过一次验证
+    pub fn with_replay_cache(&mut self, cache: Arc<dyn ReplayStore>) {
+        self.config_mut().replay_cache = Some(cache);
+    }
+
+    /// 启用按 src_id 的验证失败限流, 见 [`RateLimiter`]
+    ///
+    /// 失败票据的 src_id 未经验签, 这只是软性缓解措施, 不能替代认证。
+    pub fn with_rate_limiter(&mut self, limiter: RateLimiter) {
+        self.config_mut().rate_limiter = Some(limiter);
+    }
+
+    /// 使用十六进制公钥创建验证器, 其余为默认设置
+    ///
+    /// 公钥在构造时立即解析并校验 (格式、长度以及是否为有效的曲线点),
+    /// 配置错误在启动时即可发现, 而不是等到第一次验证票据。需要更多配置时使用
+    /// [`builder`](Self::builder)。
+    pub fn with_public_key_hex(hex_key: &str) -> Result<Self, TicketError> {
+        let mut verifier = Self::new();
+        verifier.set_public_key_hex(hex_key)?;
+        Ok(verifier)
+    }
+
+    /// 使用十六进制字符串设置公钥 (替换已有的全部公钥)
+    /// 公钥应从 API Server 获取并内置到客户端
+    pub fn set_public_key_hex(&mut self, hex_key: &str) -> Result<(), TicketError> {
+        let key = parse_public_key_hex(hex_key)?;
+        self.config_mut().public_keys = vec![TicketKey::ed25519(None, key)];
+        Ok(())
+    }
+
+    /// 从环境变量读取十六进制公钥 (替换已有的全部公钥), 适用于容器部署
+    ///
+    /// 去掉首尾空白和可选的 `0x` 前缀, 大小写均可; 变量未设置、不是合法 UTF-8 或公钥无效时
+    /// 返回 [`TicketError::ConfigurationError`], 错误信息中包含变量名。
+    pub fn set_public_key_from_env(&mut self, var_name: &str) -> Result<(), TicketError> {
+        let value = std::env::var(var_name).map_err(|e| {
+            TicketError::ConfigurationError(format!("读取环境变量 {} 失败: {}", var_name, e))
+        })?;
+        let value = value.trim();
+        let hex_key = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+        self.set_public_key_hex(hex_key).map_err(|e| {
+            TicketError::ConfigurationError(format!("环境变量 {} 中的公钥无效: {}", var_name, e))
+        })
+    }
+
+    /// 使用 PEM 格式 (`-----BEGIN PUBLIC KEY-----`) 的 Ed25519 公钥 (替换已有的全部公钥)
+    pub fn set_public_key_pem(&mut self, pem: &str) -> Result<(), TicketError> {
+        let key = parse_public_key_pem(pem)?;
+        self.config_mut().public_keys = vec![TicketKey::ed25519(None, key)];
+        Ok(())
+    }
+
+    /// 追加一个十六进制公钥
+    /// 用于密钥轮换: 新旧公钥签发的票据在过渡期内都能通过验证
+    ///
+    /// 重复加载的处理见 [`with_reject_duplicate_keys`](Self::with_reject_duplicate_keys)。
+    pub fn add_public_key_hex(&mut self, hex_key: &str) -> Result<(), TicketError> {
+        let key = parse_public_key_hex(hex_key)?;
+        self.push_key(TicketKey::ed25519(None, key))
+    }
+
+    /// 追加一个带 ID 的十六进制公钥
+    /// 票据携带 kid 时只会使用 ID 相同的公钥验签
+    ///
+    /// 该 ID 已对应另一个公钥时返回 [`TicketError::KeyIdConflict`]。
+    pub fn add_public_key_hex_with_id(&mut self, id: &str, hex_key: &str) -> Result<(), TicketError> {
+        let key = parse_public_key_hex(hex_key)?;
+        self.push_key(TicketKey::ed25519(Some(id.to_owned()), key))
+    }
+
+    /// 从 JWKS 格式的 JSON 文档追加公钥, 返回加载的公钥数量
+    ///
+    /// 只加载 `kty` 为 `OKP`、`crv` 为 `Ed25519` 的条目, `x` 为 base64url 编码的公钥,
+    /// 带 `kid` 时按 ID 加载; 其他类型的条目 (如 RSA) 跳过并记录 debug 日志。
+    /// 文档无法解析或 Ed25519 条目中的公钥无效时返回错误, 此前的条目已经加载。
+    pub fn load_jwks(&mut self, json: &str) -> Result<usize, TicketError> {
+        let document: JwksDocument = serde_json::from_str(json)
+            .map_err(|e| TicketError::ConfigurationError(format!("解析 JWKS 失败: {}", e)))?;
+        let mut loaded = 0;
+        for jwk in document.keys {
+            if jwk.kty != "OKP" || jwk.crv.as_deref() != Some("Ed25519") {
+                log::debug!(
+                    target: LOG_TARGET,
+                    "跳过非 Ed25519 的 JWKS 条目: kty={} crv={:?} kid={:?}",
+                    jwk.kty,
+                    jwk.crv,
+                    jwk.kid
+                );
+                continue;
+            }
+            let key = parse_jwk_x(jwk.x.as_deref())?;
+            self.push_key(TicketKey::ed25519(jwk.kid, key))?;
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// 追加一个委托票据的信任锚公钥 (十六进制), 见 [`Delegation`]
+    ///
+    /// 载荷携带 dlg 的票据先用信任锚验证其中的中间公钥, 再用中间公钥验证票据签名,
+    /// 不使用普通公钥; 未携带 dlg 的票据仍只用普通公钥验证。
+    /// 压缩载荷在验签前无法读取 dlg, 委托票据不能使用压缩形式。
+    pub fn add_trust_anchor_hex(&mut self, hex_key: &str) -> Result<(), TicketError> {
+        let key = parse_public_key_hex(hex_key)?;
+        self.config_mut().trust_anchors.push(key);
+        Ok(())
+    }
+
+    /// 追加已加载过的公钥时返回 [`TicketError::DuplicateKey`], 默认静默忽略
+    ///
+    /// 相同公钥以相同 ID (或都不带 ID) 再次添加时视为重复, 默认不重复加载;
+    /// 启用后, 同一公钥以不同 ID 添加也视为重复, 便于发现脚本化加载公钥时的配置错误。
+    /// 只影响之后的添加操作, 应在加载公钥之前设置。
+    pub fn with_reject_duplicate_keys(&mut self, reject: bool) {
+        self.config_mut().reject_duplicate_keys = reject;
+    }
+
+    /// 追加公钥并检查重复和公钥 ID 冲突
+    fn push_key(&mut self, key: TicketKey) -> Result<(), TicketError> {
+        let config = &self.config;
+        if let Some(kid) = key.kid.as_deref() {
+            let conflict = config
+                .public_keys
+                .iter()
+                .any(|k| k.kid.as_deref() == Some(kid) && k.key_bytes != key.key_bytes);
+            if conflict {
+                return Err(TicketError::KeyIdConflict(kid.to_owned()));
+            }
+        }
+        let mut same_key = config
+            .public_keys
+            .iter()
+            .filter(|k| key.key_bytes.is_some() && k.key_bytes == key.key_bytes);
+        let duplicate = if config.reject_duplicate_keys {
+            same_key.next().is_some()
+        } else {
+            same_key.any(|k| k.kid == key.kid)
+        };
+        match (duplicate, config.reject_duplicate_keys) {
+            (true, true) => Err(TicketError::DuplicateKey),
+            (true, false) => Ok(()),
+            (false, _) => {
+                self.config_mut().public_keys.push(key);
+                Ok(())
+            }
+        }
+    }
+
+    /// 从 API Server 获取公钥集合并替换已有的全部公钥
+    ///
+    /// 获取结果在内存中缓存, 缓存有效期内重复调用不会发起请求;
+    /// 请求失败时沿用上次成功获取的公钥, 只有从未成功获取过时才返回错误。
+    /// 需启用 `key-fetch` feature。
+    #[cfg(feature = "key-fetch")]
+    pub async fn fetch_public_keys(&mut self, url: &str) -> Result<(), TicketError> {
+        let keys = key_set::load_key_set(url).await?;
+        self.config_mut().public_keys = keys;
+        Ok(())
+    }
+
+    /// 添加使用自定义签名算法的公钥 (不替换已有公钥)
+    ///
+    /// 票据的 alg 字段需与 [`SignatureScheme::algorithm`] 一致才会使用该公钥验签。
+    pub fn add_public_key_scheme(&mut self, kid: Option<&str>, scheme: Box<dyn SignatureScheme>) {
+        self.config_mut().public_keys.push(TicketKey {
+            kid: kid.map(str::to_owned),
+            scheme: Arc::from(scheme),
+            valid_until: None,
+            key_bytes: None,
+        });
+    }
+
+    /// 追加一个只在 `valid_until` (Unix 秒, 含) 之前有效的十六进制公钥
+    ///
+    /// 用于密钥轮换的过渡期: 旧公钥带上停用时间继续加载, 到期后验证器自动不再使用,
+    /// 再通过 [`prune_expired_keys`](Self::prune_expired_keys) 从配置中移除。
+    pub fn add_public_key_hex_until(
+        &mut self,
+        kid: Option<&str>,
+        hex_key: &str,
+        valid_until: i64,
+    ) -> Result<(), TicketError> {
+        let key = parse_public_key_hex(hex_key)?;
+        let mut key = TicketKey::ed25519(kid.map(str::to_owned), key);
+        key.valid_until = Some(valid_until);
+        self.push_key(key)
+    }
+
+    /// 移除已过停用时间的公钥, 返回移除的数量
+    pub fn prune_expired_keys(&mut self) -> usize {
+        let now = self.config.clock.now_unix();
+        if self.config.public_keys.iter().all(|k| k.is_active(now)) {
+            return 0;
+        }
+        let keys = &mut self.config_mut().public_keys;
+        let before = keys.len();
+        keys.retain(|k| k.is_active(now));
+        before - keys.len()
+    }
+
+    /// 已加载公钥的指纹, 形如 `<kid>:<指纹>`, 未设置 kid 时只有指纹
+    ///
+    /// 指纹为 32 字节公钥 SHA-256 的前 8 字节 (十六进制), 不暴露公钥本身,
+    /// 可用于核对运行中的客户端信任的公钥与服务端当前启用的公钥是否一致。
+    /// 通过 [`add_public_key_scheme`](Self::add_public_key_scheme) 加载的自定义算法公钥没有指纹,
+    /// 不在其列。
+    pub fn key_fingerprints(&self) -> Vec<String> {
+        self.config
+            .public_keys
+            .iter()
+            .filter_map(|k| {
+                let fingerprint = key_fingerprint(k.key_bytes.as_ref()?);
+                Some(match k.kid.as_deref() {
+                    Some(kid) => format!("{}:{}", kid, fingerprint),
+                    None => fingerprint,
+                })
+            })
+            .collect()
+    }
+
+    /// 当前配置的摘要, 适合在启动时写入日志以确认配置
+    pub fn config_summary(&self) -> VerifierConfigSummary {
+        let config = &self.config;
+        VerifierConfigSummary {
+            prefixes: config.prefix_bases.clone(),
+            legacy_unversioned: config.legacy_unversioned,
+            keys: config
+                .public_keys
+                .iter()
+                .map(|k| KeySummary {
+                    kid: k.kid.clone(),
+                    algorithm: k.scheme.algorithm().to_owned(),
+                    fingerprint: k.key_bytes.as_ref().map(|b| key_fingerprint(b)),
+                    valid_until: k.valid_until,
+                })
+                .collect(),
+            trust_anchors: config
+                .trust_anchors
+                .iter()
+                .map(|k| key_fingerprint(k.as_bytes()))
+                .collect(),
+            clock_skew: config.clock_skew,
+            max_lifetime: config.max_lifetime,
+            max_absolute_exp: config.max_absolute_exp,
+            iat_window: config.iat_window,
+            expected_audience: config.expected_audience.clone(),
+            expected_type: config.expected_type.clone(),
+            device_binding: match config.device_binding.as_ref() {
+                None => "argument".to_owned(),
+                Some(DeviceBinding::AnyDevice) => "any".to_owned(),
+                Some(DeviceBinding::Exact(id)) => format!("exact:{}", id),
+            },
+            min_nonce_len: config.min_nonce_len,
+            max_payload_bytes: config.max_payload_bytes,
+            replay_cache: config.replay_cache.is_some(),
+            revocation_list: config.revocation_list.is_some(),
+            verify_cache: config.verify_cache.is_some(),
+            rate_limiter: config.rate_limiter.is_some(),
+        }
+    }
+
+    /// 是否已加载至少一个公钥 (或信任锚), 即可以开始验证票据
+    pub fn is_ready(&self) -> bool {
+        !self.config.public_keys.is_empty() || !self.config.trust_anchors.is_empty()
+    }
+
+    /// 未加载任何公钥时返回 [`TicketError::MissingPublicKey`]
+    ///
+    /// 用于在启动时断言配置完整, 避免直到首次连接才暴露"公钥未设置"。
+    pub fn ready_or_err(&self) -> Result<(), TicketError> {
+        if self.is_ready() {
+            Ok(())
+        } else {
+            Err(TicketError::MissingPublicKey)
+        }
+    }
+
+    /// 验证票据
+    /// 
+    /// # 参数
+    /// - `ticket`: 票据字符串 (格式: TICKET:v1:<base64url(payload)>.<base64url(signature)>)
+    /// - `my_device_id`: 本机设备 ID (用于验证 dst_id)
+    /// 
+    /// # 返回
+    /// - `Ok(TicketPayload)`: 验证成功，返回载荷
+    /// - `Err(TicketError)`: 验证失败，返回失败原因
+    pub fn verify(&self, ticket: &str, my_device_id: &str) -> Result<TicketPayload, TicketError> {
+        self.verify_at(ticket, my_device_id, self.config.clock.now_unix())
+    }
+
+    /// 直接验证字节形式的票据, 省去调用方对不可信输入做 UTF-8 校验
+    ///
+    /// 前缀检查、分段和 base64 解码都直接在字节上进行, 结果与 [`verify`](Self::verify)
+    /// 完全相同, 同样经过限流、日志和结果回调。不以任一前缀开头的输入返回
+    /// [`TicketError::BadPrefix`], 各段含非 ASCII 字节的返回 [`TicketError::Base64`]。
+    pub fn verify_bytes(
+        &self,
+        ticket: &[u8],
+        my_device_id: &str,
+    ) -> Result<TicketPayload, TicketError> {
+        let now = self.config.clock.now_unix();
+        self.verify_observed(ticket, my_device_id, now, &VerifyContext::default())
+    }
+
+    /// 在 Tokio 阻塞线程池中验证票据, 结果与 [`verify`](Self::verify) 相同
+    ///
+    /// 验签是纯 CPU 计算, 在异步执行器上直接调用 `verify` 时, 大量连接同时到达会占住
+    /// 工作线程、拖慢其他任务。异步服务端的连接处理路径应使用本方法; 同步代码或单次
+    /// 零星验证仍可直接调用 `verify`, 省去线程切换的开销。
+    ///
+    /// 内部只克隆验证器句柄 (引用计数), 多个任务可以并发调用同一实例。
+    /// 验证过程中发生 panic 时在调用方任务中重新抛出。
+    #[cfg(feature = "tokio")]
+    pub async fn verify_async(
+        &self,
+        ticket: &str,
+        my_device_id: &str,
+    ) -> Result<TicketPayload, TicketError> {
+        let verifier = self.clone();
+        let ticket = ticket.to_owned();
+        let my_device_id = my_device_id.to_owned();
+        hbb_common::tokio::task::spawn_blocking(move || verifier.verify(&ticket, &my_device_id))
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+
+    /// 批量验证票据, 每项为 (票据, 本机设备 ID), 结果与输入顺序一致
+    ///
+    /// 整批只读取一次时间源, 所有票据按同一时刻判断有效期, 启用的缓存在整批内共用。
+    pub fn verify_batch(&self, tickets: &[(&str, &str)]) -> Vec<Result<TicketPayload, TicketError>> {
+        let now = self.config.clock.now_unix();
+        tickets
+            .iter()
+            .map(|(ticket, my_device_id)| self.verify_at(ticket, my_device_id, now))
+            .collect()
+    }
+
+    /// 逐行验证票据, 每行一张, 按需读取, 内存占用与输入大小无关
+    ///
+    /// 每行产生一个结果, 顺序与输入一致: 空行和不带票据前缀的行返回
+    /// [`TicketError::BadPrefix`], 内容不是 UTF-8 的行返回 [`TicketError::Io`],
+    /// 都不会中止迭代。行首尾的空白会被忽略。`reader` 本身读取失败时产生一个
+    /// [`TicketError::Io`] 后结束迭代, 不会对持续出错的输入无限重试。
+    ///
+    /// 每行最多读入按载荷大小上限能接受的最长票据再加少量空白, 超长的行返回
+    /// [`TicketError::PayloadTooLarge`], 其余部分直接跳过而不缓存。
+    pub fn verify_reader<'a, R: BufRead + 'a>(
+        &'a self,
+        mut reader: R,
+        my_device_id: &'a str,
+    ) -> impl Iterator<Item = Result<TicketPayload, TicketError>> + 'a {
+        let max_line = self.max_ticket_len() + LINE_WHITESPACE_SLACK;
+        let mut line = Vec::new();
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            line.clear();
+            let read = (&mut reader)
+                .take(max_line as u64 + 1)
+                .read_until(b'\n', &mut line);
+            match read {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => {
+                    done = true;
+                    return Some(Err(TicketError::Io(e)));
+                }
+            }
+            if line.len() > max_line && line.last() != Some(&b'\n') {
+                return Some(match skip_line(&mut reader) {
+                    Ok(()) => Err(TicketError::PayloadTooLarge),
+                    Err(e) => {
+                        done = true;
+                        Err(TicketError::Io(e))
+                    }
+                });
+            }
+            Some(self.verify_line(&line, my_device_id))
+        })
+    }
+
+    /// [`verify_reader`](Self::verify_reader) 读入的一行 (可能带换行符)
+    fn verify_line(&self, line: &[u8], my_device_id: &str) -> Result<TicketPayload, TicketError> {
+        let line = std::str::from_utf8(line).map_err(|e| {
+            TicketError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })?;
+        let ticket = line.trim();
+        if !self.is_ticket(ticket.as_bytes()) {
+            return Err(TicketError::BadPrefix);
+        }
+        self.verify(ticket, my_device_id)
+    }
+
+    /// 当前配置下能通过长度检查的最长票据文本 (字节)
+    fn max_ticket_len(&self) -> usize {
+        let config = &self.config;
+        let prefix = config
+            .prefix_bases
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or(0);
+        // 前缀 + 版本标记 + 压缩标记 + 载荷段 (含填充) + 分隔符 + 签名段
+        prefix
+            + TicketVersion::V2.marker().len()
+            + 1
+            + config.max_payload_bytes.div_ceil(3) * 4
+            + 1
+            + MAX_SIGNATURE_B64_LEN
+    }
+
+    /// 验证票据, 同时返回剩余有效时间
+    ///
+    /// 剩余时间使用与验证相同的时间源计算, 可直接用于安排重连或缓存过期。
+    pub fn verify_detailed(
+        &self,
+        ticket: &str,
+        my_device_id: &str,
+    ) -> Result<VerifiedTicket, TicketError> {
+        let now = self.config.clock.now_unix();
+        let timings = Cell::default();
+        let matched_key = Cell::default();
+        let ctx = VerifyContext {
+            timings: Some(&timings),
+            expiry_grace: true,
+            matched_key: Some(&matched_key),
+            ..Default::default()
+        };
+        let payload = self.verify_observed(ticket.as_bytes(), my_device_id, now, &ctx)?;
+        let remaining = payload.exp.saturating_sub(now).max(0);
+        let near_expiry = self
+            .config
+            .soft_expiry
+            .is_some_and(|threshold| remaining < threshold);
+        let expired_within_grace = payload.exp < now.saturating_sub(self.config.clock_skew);
+        Ok(VerifiedTicket {
+            payload,
+            remaining: Duration::from_secs(remaining as u64),
+            timings: timings.get(),
+            near_expiry,
+            expired_within_grace,
+            matched_kid: matched_key.take(),
+        })
+    }
+
+    /// 验证票据并汇总各项检查结果, 供审计和运维界面展示
+    ///
+    /// 先验签, 只有签名有效时才根据声明内容判断过期和目标设备;
+    /// 不登记也不检查防重放缓存, 审计不会消耗票据。
+    pub fn verify_audit(&self, ticket: &str, my_device_id: &str) -> VerifyReport {
+        let ticket = &*self.versioned(ticket.as_bytes());
+        let now = self.config.clock.now_unix();
+        let mut report = VerifyReport {
+            signature_ok: false,
+            expired: false,
+            device_match: false,
+            decoded_claims: None,
+            accepted: false,
+            first_error: None,
+        };
+
+        let payload = match self.decode_verified(ticket, KeyEpoch::Now(now)) {
+            Ok(payload) => payload,
+            Err(e) => {
+                if !matches!(e, TicketError::PayloadTooLarge) {
+                    report.decoded_claims = self.claims_unverified(ticket);
+                }
+                report.first_error = Some(e);
+                return report;
+            }
+        };
+
+        report.signature_ok = true;
+        report.expired = payload.exp < now.saturating_sub(self.config.clock_skew);
+        report.device_match = self
+            .expected_device(my_device_id)
+            .map_or(true, |expected| payload.targets(expected));
+        let ctx = VerifyContext::default();
+        match self.check_stateless_claims(&payload, my_device_id, Some(now), &ctx) {
+            Ok(()) => report.accepted = true,
+            Err(e) => report.first_error = Some(e),
+        }
+        report.decoded_claims = Some(payload);
+        report
+    }
+
+    /// 验证票据的签名和内容, 但不检查有效期, 供离线审计历史票据使用
+    ///
+    /// **绝不能用于实时授权**: 已过期、尚未生效的票据都会通过, 结果只说明票据
+    /// "确由签发方签出且内容完整", 不说明"当前有效"。
+    ///
+    /// 照常检查签名、结构、目标设备、受众、类型、nonce 和吊销列表, 跳过 exp / nbf / iat
+    /// 与当前时间的比较; exp 早于 iat 和超出最长有效期仍会拒绝。公钥停用时间和委托
+    /// 凭据有效期按票据的 iat 判断, 签发后才停用的公钥签出的票据照常通过;
+    /// [`set_min_iat`](Self::set_min_iat) 的截止时间属于实时策略, 这里不适用。
+    /// 不登记也不检查防重放缓存, 绑定连接的票据 (cb / src_cidr) 因无法核对而被拒绝。
+    pub fn verify_ignore_expiry(
+        &self,
+        ticket: &str,
+        my_device_id: &str,
+    ) -> Result<TicketPayload, TicketError> {
+        let ticket = &*self.versioned(ticket.as_bytes());
+        let payload = self.decode_verified(ticket, KeyEpoch::IssuedAt)?;
+        self.check_stateless_claims(&payload, my_device_id, None, &VerifyContext::default())?;
+        Ok(payload)
+    }
+
+    /// 以指定的当前时间 (Unix 秒) 验证票据
+    ///
+    /// 执行与 [`verify`](Self::verify) 完全相同的检查, 所有时间比较都使用 `now_unix`
+    /// 而不读取时间源, 用于测试有效期边界或回放历史连接记录
+    /// ("该票据在时刻 T 是否有效")。
+    pub fn verify_at(
+        &self,
+        ticket: &str,
+        my_device_id: &str,
+        now_unix: i64,
+    ) -> Result<TicketPayload, TicketError> {
+        let ctx = VerifyContext::default();
+        self.verify_observed(ticket.as_bytes(), my_device_id, now_unix, &ctx)
+    }
+
+    /// 验证绑定到当前连接的票据
+    ///
+    /// `binding` 是本端观察到的握手值, 计算方式见 [`channel_binding`]。
+    /// 载荷携带 cb 时必须与之一致, 否则返回 [`TicketError::ChannelBindingMismatch`];
+    /// 载荷不含 cb 时跳过该检查, 与旧票据兼容。
+    ///
+    /// 其他验证入口不提供绑定值, 遇到携带 cb 的票据一律拒绝。
+    pub fn verify_with_channel_binding(
+        &self,
+        ticket: &str,
+        my_device_id: &str,
+        binding: &[u8],
+    ) -> Result<TicketPayload, TicketError> {
+        let now = self.config.clock.now_unix();
+        let ctx = VerifyContext {
+            channel_binding: Some(binding),
+            ..Default::default()
+        };
+        self.verify_observed(ticket.as_bytes(), my_device_id, now, &ctx)
+    }
+
+    /// 验证票据并检查对端地址
+    ///
+    /// 载荷携带 src_cidr 时 `peer_ip` 必须落在该网络内, 否则返回
+    /// [`TicketError::AddressNotAllowed`]; 载荷不含 src_cidr 时跳过该检查。
+    ///
+    /// 其他验证入口不提供对端地址, 遇到携带 src_cidr 的票据一律拒绝。
+    pub fn verify_from_peer(
+        &self,
+        ticket: &str,
+        my_device_id: &str,
+        peer_ip: IpAddr,
+    ) -> Result<TicketPayload, TicketError> {
+        let now = self.config.clock.now_unix();
+        let ctx = VerifyContext {
+            ip: Some(peer_ip),
+            ..Default::default()
+        };
+        self.verify_observed(ticket.as_bytes(), my_device_id, now, &ctx)
+    }
+
+    /// 验证 [`encode_binary`] 编码的二进制票据
+    ///
+    /// 先转换为本验证器接受的文本格式再验证, 检查项 (包括防重放、限流和日志) 与
+    /// [`verify`](Self::verify) 完全相同。帧结构错误时返回 [`TicketError::MalformedStructure`]。
+    pub fn verify_binary(
+        &self,
+        frame: &[u8],
+        my_device_id: &str,
+    ) -> Result<TicketPayload, TicketError> {
+        let (version, payload, signature) = decode_binary(frame)?;
+        let config = &self.config;
+        let base = config
+            .prefix_bases
+            .first()
+            .map_or(DEFAULT_PREFIX_BASE, String::as_str);
+        let engine = config.base64_variant.engine();
+        let ticket = format!(
+            "{}{}{}.{}",
+            base,
+            version.marker(),
+            engine.encode(payload),
+            engine.encode(signature)
+        );
+        self.verify(&ticket, my_device_id)
+    }
+
+    /// 执行验证并在配置了回调时上报结果
+    fn verify_observed(
+        &self,
+        ticket: &[u8],
+        my_device_id: &str,
+        now_unix: i64,
+        ctx: &VerifyContext<'_>,
+    ) -> Result<TicketPayload, TicketError> {
+        let ticket = &*self.versioned(ticket);
+        let Some(hook) = self.config.on_result.as_ref() else {
+            return self.verify_logged(ticket, my_device_id, now_unix, ctx);
+        };
+
+        let start = Instant::now();
+        let result = self.verify_logged(ticket, my_device_id, now_unix, ctx);
+        let elapsed = start.elapsed();
+
+        // 失败时尽量解出载荷以便统计, 这些字段未经验签
+        let payload = match &result {
+            Ok(payload) => Some(payload),
+            Err(_) => self.claims_once(ticket, ctx),
+        };
+        let outcome = VerifyOutcome {
+            error: result.as_ref().err(),
+            src_id: payload.map(|p| p.src_id.as_str()),
+            dst_id: payload.map(|p| &p.dst_id),
+            elapsed,
+        };
+        if catch_unwind(AssertUnwindSafe(|| hook(&outcome))).is_err() {
+            log::error!(target: LOG_TARGET, "票据验证结果回调发生 panic, 已忽略");
+        }
+
+        result
+    }
+
+    /// 执行验证并记录结果: 成功为 info, 认证失败为 warn, 结构性拒绝 (不像票据的输入) 为 debug
+    fn verify_logged(
+        &self,
+        ticket: &[u8],
+        my_device_id: &str,
+        now: i64,
+        ctx: &VerifyContext<'_>,
+    ) -> Result<TicketPayload, TicketError> {
+        let result = self.verify_limited(ticket, my_device_id, now, ctx);
+        match &result {
+            Ok(payload) => log::info!(
+                target: LOG_TARGET,
+                "票据验证成功: fingerprint={}, src_id={}",
+                payload.fingerprint(),
+                payload.src_id
+            ),
+            Err(e) => {
+                // 失败票据的声明未经验签, 只用于关联日志
+                let claims = self.claims_once(ticket, ctx);
+                log::log!(
+                    target: LOG_TARGET,
+                    e.log_level(),
+                    "票据验证失败: code={}, fingerprint={}, src_id={}: {}",
+                    e.code(),
+                    claims.map_or_else(|| "-".to_owned(), |c| c.fingerprint()),
+                    claims.map_or("-", |c| c.src_id.as_str()),
+                    e
+                );
+            }
+        }
+        result
+    }
+
+    /// 配置了限流器时先检查票据声称的 src_id 是否处于冷却期, 并在验证后更新失败计数
+    fn verify_limited(
+        &self,
+        ticket: &[u8],
+        my_device_id: &str,
+        now: i64,
+        ctx: &VerifyContext<'_>,
+    ) -> Result<TicketPayload, TicketError> {
+        let Some(limiter) = self.config.rate_limiter.as_ref() else {
+            return self.verify_checked(ticket, my_device_id, now, ctx);
+        };
+
+        // 无法解出 src_id 的输入 (包括超出载荷大小上限的) 不计数
+        let claimed_src_id = self.claims_once(ticket, ctx).map(|p| p.src_id.as_str());
+        if let Some(src_id) = claimed_src_id {
+            if limiter.is_limited(src_id, now) {
+                return Err(TicketError::RateLimited);
+            }
+        }
+
+        let result = self.verify_checked(ticket, my_device_id, now, ctx);
+        match (&result, claimed_src_id) {
+            (Ok(payload), _) => limiter.record_success(&payload.src_id),
+            (Err(_), Some(src_id)) => limiter.record_failure(src_id, now),
+            (Err(_), None) => {}
+        }
+        result
+    }
+
+    /// 执行全部验证步骤
+    fn verify_checked(
+        &self,
+        ticket: &[u8],
+        my_device_id: &str,
+        now: i64,
+        ctx: &VerifyContext<'_>,
+    ) -> Result<TicketPayload, TicketError> {
+        let config = &self.config;
+        let cache = config
+            .verify_cache
+            .as_ref()
+            .filter(|_| config.replay_cache.is_none() && config.revocation_list.is_none());
+        let decoded = match cache.and_then(|c| c.get(ticket, now)) {
+            Some(decoded) => decoded,
+            None => {
+                let mut timings = VerifyTimings::default();
+                let decoded = self.decode_timed(ticket, KeyEpoch::Now(now), &mut timings);
+                if let Some(cell) = ctx.timings {
+                    cell.set(timings);
+                }
+                let decoded = decoded?;
+                if let Some(cache) = cache {
+                    // 缓存不能比任何带停用时间的公钥活得更久, 否则停用后缓存仍会放行
+                    let key_deadline =
+                        config.public_keys.iter().filter_map(|k| k.valid_until).min();
+                    let expires_at = decoded
+                        .payload
+                        .exp
+                        .saturating_add(config.clock_skew)
+                        .min(key_deadline.unwrap_or(i64::MAX));
+                    cache.insert(ticket, &decoded, expires_at, now);
+                }
+                decoded
+            }
+        };
+
+        self.check_claims(&decoded.payload, my_device_id, now, ctx)?;
+        if let Some(cell) = ctx.matched_key {
+            cell.set(decoded.matched_key);
+        }
+        Ok(decoded.payload)
+    }
+
+    /// 解码票据并验证签名, 返回已验签的载荷
+    fn decode_verified(
+        &self,
+        ticket: &[u8],
+        epoch: KeyEpoch,
+    ) -> Result<TicketPayload, TicketError> {
+        self.decode_timed(ticket, epoch, &mut VerifyTimings::default())
+            .map(|decoded| decoded.payload)
+    }
+
+    /// 同 [`claims_unverified`](Self::claims_unverified), 同一次验证中只解码一次
+    fn claims_once<'c>(
+        &self,
+        ticket: &[u8],
+        ctx: &'c VerifyContext<'_>,
+    ) -> Option<&'c TicketPayload> {
+        ctx.unverified
+            .get_or_init(|| self.claims_unverified(ticket))
+            .as_ref()
+    }
+
+    /// 按本验证器的前缀、编码方式和载荷大小上限解出载荷, 不验签, 结果**不可信**
+    fn claims_unverified(&self, ticket: &[u8]) -> Option<TicketPayload> {
+        let config = &self.config;
+        decode_unverified(
+            ticket,
+            &config.prefix_bases,
+            config.base64_variant,
+            config.max_payload_bytes,
+        )
+        .ok()
+    }
+
+    /// 同 [`decode_verified`](Self::decode_verified), 并把解析和验签的耗时累加到 `timings`,
+    /// 同时返回验签所用公钥的标识
+    fn decode_timed(
+        &self,
+        ticket: &[u8],
+        epoch: KeyEpoch,
+        timings: &mut VerifyTimings,
+    ) -> Result<DecodedTicket, TicketError> {
+        let config = &self.config;
+        // 检查公钥是否已设置
+        self.ready_or_err()?;
+        // 在 `at` 时刻已过停用时间的公钥不参与验签
+        let keys_active_at =
+            |at: i64| config.public_keys.iter().filter(move |k| k.is_active(at));
+
+        let RawTicket {
+            version,
+            compressed,
+            payload_bytes,
+            signature,
+        } = timed(&mut timings.parse, || {
+            decode_segments(
+                ticket,
+                &config.prefix_bases,
+                config.max_payload_bytes,
+                config.base64_variant,
+            )
+        })?;
+
+        let decode = |bytes: &[u8]| {
+            if config.deny_unknown_fields {
+                version.decode_payload_strict(bytes)
+            } else {
+                version.decode_payload(bytes)
+            }
+        };
+
+        if compressed {
+            // 压缩载荷先验签再解压, 无法预先读取 kid、alg 和 iat, 依次尝试每个公钥;
+            // 按 iat 判断停用时间时先不筛选, 解压后的一致性检查再补上
+            let authentic = timed(&mut timings.signature, || {
+                keys_active_at(epoch.at(i64::MIN)).any(|k| {
+                    k.scheme.verify(&payload_bytes, &signature).is_ok()
+                        || k
+                            .scheme
+                            .verify_with(ALG_ED25519PH, &payload_bytes, &signature)
+                            .is_ok()
+                })
+            });
+            if !authentic {
+                return Err(TicketError::SignatureInvalid);
+            }
+            let payload = timed(&mut timings.parse, || {
+                decode(&inflate(&payload_bytes, config.max_payload_bytes)?)
+            })?;
+            // 载荷声明的 kid 和 alg 必须与实际验签的公钥一致
+            let alg = payload.alg.as_deref().unwrap_or(ALG_ED25519);
+            let consistent = timed(&mut timings.signature, || {
+                keys_active_at(epoch.at(payload.iat)).find(|k| {
+                    (payload.kid.is_none() || k.kid == payload.kid)
+                        && k.scheme.verify_with(alg, &payload_bytes, &signature).is_ok()
+                })
+            });
+            let Some(key) = consistent else {
+                return Err(TicketError::SignatureInvalid);
+            };
+            return Ok(DecodedTicket {
+                matched_key: key.label(),
+                payload,
+            });
+        }
+
+        // 解析载荷 (此时尚未验签, 只用于读取 kid 和 alg)
+        let payload = timed(&mut timings.parse, || decode(&payload_bytes))?;
+        let alg = payload.alg.as_deref().unwrap_or(ALG_ED25519);
+
+        if matches!(alg, ALG_ED25519 | ALG_ED25519PH) && signature.len() != 64 {
+            return Err(TicketError::BadSignatureLength(signature.len()));
+        }
+
+        if let Some(delegation) = payload.dlg.as_ref() {
+            let key = timed(&mut timings.signature, || {
+                let key = self.verify_delegation(delegation, payload.exp, epoch.at(payload.iat))?;
+                Ed25519Scheme::new(key)
+                    .verify_with(alg, &payload_bytes, &signature)
+                    .map(|()| key)
+                    .map_err(|()| TicketError::SignatureInvalid)
+            })?;
+            return Ok(DecodedTicket {
+                matched_key: Some(key_fingerprint(key.as_bytes())),
+                payload,
+            });
+        }
+
+        // 验证签名: 指定 kid 时只使用对应公钥, 否则依次尝试同一算法的每个公钥
+        let active_keys = || keys_active_at(epoch.at(payload.iat));
+        let verified = match payload.kid.as_deref() {
+            Some(kid) => {
+                let key = active_keys()
+                    .find(|k| k.kid.as_deref() == Some(kid))
+                    .ok_or_else(|| TicketError::UnknownKeyId(kid.to_owned()))?;
+                timed(&mut timings.signature, || {
+                    key.scheme.verify_with(alg, &payload_bytes, &signature).is_ok()
+                })
+                .then_some(key)
+            }
+            None => timed(&mut timings.signature, || {
+                active_keys()
+                    .find(|k| k.scheme.verify_with(alg, &payload_bytes, &signature).is_ok())
+            }),
+        };
+        let Some(key) = verified else {
+            return Err(TicketError::SignatureInvalid);
+        };
+
+        Ok(DecodedTicket {
+            matched_key: key.label(),
+            payload,
+        })
+    }
+
+    /// 用信任锚验证委托凭据, 返回可用于验证票据签名的中间公钥
+    ///
+    /// `ticket_exp` 来自尚未验签的载荷, 只用于拒绝有效期超出委托范围的票据。
+    fn verify_delegation(
+        &self,
+        delegation: &Delegation,
+        ticket_exp: i64,
+        now: i64,
+    ) -> Result<VerifyingKey, TicketError> {
+        let config = &self.config;
+        let key = parse_public_key_hex(&delegation.key)
+            .map_err(|_| TicketError::DelegationInvalid)?;
+        let sig: [u8; 64] = URL_SAFE_NO_PAD
+            .decode(&delegation.sig)
+            .ok()
+            .and_then(|sig| sig.try_into().ok())
+            .ok_or(TicketError::DelegationInvalid)?;
+        let msg = delegation_message(key.as_bytes(), delegation.exp);
+        let authorized = config
+            .trust_anchors
+            .iter()
+            .any(|anchor| anchor.verify_strict(&msg, &Signature::from_bytes(&sig)).is_ok());
+        if !authorized {
+            return Err(TicketError::DelegationInvalid);
+        }
+        if delegation.exp < now.saturating_sub(config.clock_skew) {
+            return Err(TicketError::DelegationExpired);
+        }
+        if ticket_exp > delegation.exp {
+            return Err(TicketError::DelegationInvalid);
+        }
+        Ok(key)
+    }
+
+    /// 检查已验签载荷中的各项声明, 通过后登记 nonce
+    fn check_claims(
+        &self,
+        payload: &TicketPayload,
+        my_device_id: &str,
+        now: i64,
+        ctx: &VerifyContext<'_>,
+    ) -> Result<(), TicketError> {
+        self.check_stateless_claims(payload, my_device_id, Some(now), ctx)?;
+        self.check_replay(payload, now)
+    }
+
+    /// 需要比对的目标设备 ID, None 表示不检查
+    fn expected_device<'a>(&'a self, my_device_id: &'a str) -> Option<&'a str> {
+        match self.config.device_binding.as_ref() {
+            Some(DeviceBinding::AnyDevice) => None,
+            Some(DeviceBinding::Exact(id)) => Some(id.as_str()),
+            None => Some(my_device_id),
+        }
+    }
+
+    /// 检查不依赖防重放状态的各项声明
+    ///
+    /// `now` 为 None 时跳过与当前时间的比较 (exp / nbf / iat) 和 iat 截止时间,
+    /// 只用于离线审计。
+    fn check_stateless_claims(
+        &self,
+        payload: &TicketPayload,
+        my_device_id: &str,
+        now: Option<i64>,
+        ctx: &VerifyContext<'_>,
+    ) -> Result<(), TicketError> {
+        let config = &self.config;
+        // 时间字段来自签发方, 下面的运算一律饱和, 极端取值不能导致溢出 panic
+        // 时钟早于 Unix 纪元 (如 RTC 掉电复位) 时无法判断有效期, 直接拒绝
+        if now.is_some_and(|now| now < 0) {
+            return Err(TicketError::ClockError);
+        }
+
+        if config.strict_claims {
+            if payload.nonce.is_empty() {
+                return Err(TicketError::MissingRequiredClaim("nonce"));
+            }
+            if payload.kid.is_none() {
+                return Err(TicketError::MissingRequiredClaim("kid"));
+            }
+        }
+
+        // exp 早于 iat 说明签发方有问题或票据被构造, 先于过期检查拒绝
+        if payload.exp < payload.iat {
+            return Err(TicketError::TimestampsInconsistent);
+        }
+
+        if let Some(now) = now {
+            // 检查过期时间 (允许时钟偏差, 需要时再加上宽限期)
+            let grace = if ctx.expiry_grace { config.expiry_grace } else { 0 };
+            if payload.exp < now.saturating_sub(config.clock_skew).saturating_sub(grace) {
+                return Err(TicketError::Expired);
+            }
+
+            // 检查过期时间跨度
+            if let Some(horizon) = config.max_absolute_exp {
+                if payload.exp.saturating_sub(now) > horizon {
+                    return Err(TicketError::ExpTooFar);
+                }
+            }
+
+            // 检查生效时间 (允许时钟偏差)
+            if let Some(nbf) = payload.nbf {
+                if now.saturating_add(config.clock_skew) < nbf {
+                    return Err(TicketError::NotYetValid);
+                }
+            }
+
+            // 检查签发时间: 设置了窗口时 iat 必须在 now ± 窗口内;
+            // 否则只检查远超当前时间的情况 (签发方时钟异常或票据被伪造)
+            if let Some(window) = config.iat_window {
+                if payload.iat.abs_diff(now) > window as u64 {
+                    return Err(TicketError::IssuedTooFarFromNow);
+                }
+            } else if payload.iat > now.saturating_add(config.clock_skew) {
+                return Err(TicketError::IssuedInFuture);
+            }
+        }
+
+        // 检查有效期上限
+        if let Some(max_lifetime) = config.max_lifetime {
+            if payload.exp.saturating_sub(payload.iat) > max_lifetime {
+                return Err(TicketError::LifetimeTooLong);
+            }
+        }
+
+        // 检查签发截止时间 (实时策略, 离线审计不适用)
+        if now.is_some() && payload.iat < config.min_iat.load(Ordering::Relaxed) {
+            return Err(TicketError::IssuedBeforeCutoff);
+        }
+
+        // 检查设备 ID 格式, 空 ID 不能进入下面的设备绑定逻辑; 哈希形式的 dst_id 必须带盐
+        let dst_ok = match payload.dst_id_hash {
+            Some(_) => payload.dst_salt.is_some(),
+            None => payload.dst_id.is_well_formed(&config.device_id_format),
+        };
+        if !config.device_id_format.accepts(&payload.src_id) || !dst_ok {
+            return Err(TicketError::MalformedDeviceId);
+        }
+
+        // 检查目标设备 ID
+        if let Some(expected) = self.expected_device(my_device_id) {
+            if !payload.targets(expected) {
+                return Err(TicketError::DeviceMismatch {
+                    expected: expected.to_owned(),
+                    actual: payload
+                        .dst_id_hash
+                        .clone()
+                        .unwrap_or_else(|| payload.dst_id.to_string()),
+                });
+            }
+        }
+
+        // 检查受众
+        if let Some(expected) = config.expected_audience.as_ref() {
+            if !payload.aud.as_ref().is_some_and(|aud| aud.contains(expected)) {
+                return Err(TicketError::AudienceMismatch);
+            }
+        }
+
+        // 检查票据类型
+        if let Some(expected) = config.expected_type.as_ref() {
+            if payload.typ.as_ref() != Some(expected) {
+                return Err(TicketError::WrongTicketType);
+            }
+        }
+
+        // 检查通道绑定
+        if let Some(cb) = payload.cb.as_deref() {
+            let bound = ctx
+                .channel_binding
+                .is_some_and(|binding| ct_eq(cb, &channel_binding(binding)));
+            if !bound {
+                return Err(TicketError::ChannelBindingMismatch);
+            }
+        }
+
+        // 检查对端地址; 签发方写入的 CIDR 无法解析时同样拒绝
+        if let Some(cidr) = payload.src_cidr.as_deref() {
+            let allowed = ctx.ip.is_some_and(|ip| {
+                IpCidr::from_str(cidr).is_ok_and(|cidr| cidr.contains(ip))
+            });
+            if !allowed {
+                return Err(TicketError::AddressNotAllowed);
+            }
+        }
+
+        // 检查 nonce 长度
+        if payload.nonce.chars().count() < config.min_nonce_len {
+            return Err(TicketError::WeakNonce);
+        }
+
+        // 检查吊销列表
+        if let Some(list) = config.revocation_list.as_ref() {
+            if list.is_revoked(payload) {
+                return Err(TicketError::Revoked);
+            }
+        }
+
+        // 一次性票据依赖防重放缓存, 未启用时拒绝而不是静默允许重复使用
+        if payload.once && config.replay_cache.is_none() {
+            return Err(TicketError::ConfigurationError(
+                "一次性票据需要启用防重放缓存".to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 登记 nonce, 已使用过时拒绝
+    fn check_replay(&self, payload: &TicketPayload, now: i64) -> Result<(), TicketError> {
+        let config = &self.config;
+        // 其余检查全部通过后再登记 nonce, 避免无效票据占用
+        if let Some(cache) = config.replay_cache.as_ref() {
+            let keep_until = payload
+                .exp
+                .saturating_add(config.clock_skew)
+                .saturating_add(config.expiry_grace);
+            if !cache.check_and_insert(&payload.nonce, keep_until, now) {
+                return Err(if payload.once {
+                    TicketError::AlreadyUsed
+                } else {
+                    TicketError::ReplayDetected
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TicketVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 计算载荷 cb 字段的值: `base64url(SHA-256(binding))`, 不带填充
+///
+/// `binding` 是通信双方都能观察到、且每个连接不同的握手值, 例如被控端在本次握手中
+/// 使用的临时公钥或连接 ID 的原始字节。签发方和验证方必须对同一字节序列调用本函数,
+/// 不能一方使用十六进制字符串而另一方使用原始字节。
+pub fn channel_binding(binding: &[u8]) -> String {
+    b64_encode(&Sha256::digest(binding))
+}
+
+/// 转换为 Unix 秒, 早于纪元的时间返回负数而不是 panic
+fn unix_time(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => {
+            let d = e.duration();
+            -(d.as_secs() as i64) - if d.subsec_nanos() > 0 { 1 } else { 0 }
+        }
+    }
+}
+
+/// 计算票据 dst_id_hash 字段的值: `hex(SHA-256(salt || device_id))`
+///
+/// salt 和设备 ID 均按 UTF-8 字节直接拼接, 中间没有分隔符; 输出为 64 个小写十六进制字符
+/// (验证时不区分大小写)。签发方应为每张票据随机生成盐 (如 16 字节随机数的十六进制),
+/// 避免同一设备的票据可以互相关联。
+pub fn hash_device_id(salt: &str, device_id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(device_id.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// 常量时间字符串比较, 避免按内容泄露比较耗时
+/// (长度不同时直接返回 false, 长度本身不视为机密)
+fn ct_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// 执行 `f` 并把耗时累加到 `span`
+fn timed<T>(span: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    *span += start.elapsed();
+    result
+}
+
+fn b64_encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// v1 票据载荷的规范 JSON 序列化: 对象键按字节序排列, 不含任何多余空白
+///
+/// [`TicketSigner::sign`] 使用该格式; 其它语言实现的签发方应输出相同字节, 便于比对和排查。
+/// 注意验签针对的是收到的载荷字节本身, 验证器不会把载荷重新序列化后再比较,
+/// 因此非规范格式的载荷只要签名正确同样可以通过验证。
+pub fn canonical_json(payload: &TicketPayload) -> Vec<u8> {
+    let value = serde_json::to_value(payload).expect("票据载荷序列化失败");
+    let mut out = Vec::new();
+    write_canonical(&value, &mut out);
+    out
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut Vec<u8>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            out.push(b'{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key).expect("写入内存缓冲区不会失败");
+                out.push(b':');
+                write_canonical(value, out);
+            }
+            out.push(b'}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(b']');
+        }
+        scalar => serde_json::to_writer(&mut *out, scalar).expect("写入内存缓冲区不会失败"),
+    }
+}
+
+/// 跳过 `reader` 中当前行的剩余部分 (含换行符), 不缓存跳过的内容
+fn skip_line<R: BufRead>(reader: &mut R) -> std::io::Result<()> {
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            return Ok(());
+        }
+        match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                reader.consume(i + 1);
+                return Ok(());
+            }
+            None => {
+                let n = buf.len();
+                reader.consume(n);
+            }
+        }
+    }
+}
+
+/// 无填充 base64 编码长度对应的解码后字节数: 每 4 个字符对应 3 个字节
+fn b64_decoded_len(encoded_len: usize) -> usize {
+    encoded_len / 4 * 3 + encoded_len % 4 * 3 / 4
+}
+
+/// 检查前缀并分割出 base64url 编码的载荷段和签名段
+///
+/// 直接在字节上分割, 不要求输入是合法 UTF-8; 各段中的非 ASCII 字节在 base64 解码时报错。
+fn split_segments<'a>(
+    ticket: &'a [u8],
+    prefix_bases: &[impl AsRef<str>],
+) -> Result<(TicketVersion, &'a [u8], &'a [u8]), TicketError> {
+    let blank = |bytes: &[u8]| bytes.iter().all(u8::is_ascii_whitespace);
+    // 上游偶尔传入空密码, 单独报告以区分"未提供凭据"和"凭据错误"
+    if blank(ticket) {
+        return Err(TicketError::EmptyTicket);
+    }
+    let (version, content) = split_version(ticket, prefix_bases).ok_or(TicketError::BadPrefix)?;
+    if blank(content) {
+        return Err(TicketError::EmptyTicket);
+    }
+    // base64url 不会产生 '.', 必须恰好只有一个分隔符
+    let separator = content
+        .iter()
+        .position(|&b| b == b'.')
+        .ok_or(TicketError::MissingSeparator)?;
+    let (payload_b64, signature_b64) = (&content[..separator], &content[separator + 1..]);
+    if signature_b64.contains(&b'.') {
+        return Err(TicketError::MalformedStructure);
+    }
+    Ok((version, payload_b64, signature_b64))
+}
+
+/// 结构解析和 base64 解码后的票据, 尚未验签
+struct RawTicket {
+    version: TicketVersion,
+    /// 载荷是否经过 DEFLATE 压缩
+    compressed: bool,
+    /// 签名覆盖的原始字节 (压缩票据为压缩后的字节)
+    payload_bytes: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+/// 分割票据并解码载荷和签名, 解码前按 `max_payload_bytes` 和签名段上限检查长度
+fn decode_segments(
+    ticket: &[u8],
+    prefix_bases: &[impl AsRef<str>],
+    max_payload_bytes: usize,
+    base64: Base64Variant,
+) -> Result<RawTicket, TicketError> {
+    let (version, payload_b64, signature_b64) = split_segments(ticket, prefix_bases)?;
+
+    // 解码前检查长度, 避免为超大输入分配内存
+    if b64_decoded_len(payload_b64.len()) > max_payload_bytes {
+        return Err(TicketError::PayloadTooLarge);
+    }
+    if signature_b64.len() > MAX_SIGNATURE_B64_LEN {
+        return Err(TicketError::MalformedStructure);
+    }
+
+    let (compressed, payload_b64) = strip_compressed_marker(version, payload_b64);
+    Ok(RawTicket {
+        version,
+        compressed,
+        payload_bytes: base64.decode(payload_b64)?,
+        signature: base64.decode(signature_b64)?,
+    })
+}
+
+/// 把载荷和签名编码为紧凑的二进制帧, 用于容量有限的二维码等场景
+///
+/// 帧格式: 版本号 (1 字节, v1 为 1、v2 为 2) || 载荷长度 (2 字节大端序) || 载荷 ||
+/// 签名 (64 字节), 比文本格式少去前缀和 base64 的开销。载荷与签名和文本票据相同
+/// (可由 [`split_ticket`] 得到), 不支持压缩载荷。载荷字节本身不标明编码, `version`
+/// 须与载荷编码一致 (v1 为 JSON, v2 为 CBOR), 验证器据此选择解码方式。
+/// 载荷超过 65535 字节时返回 [`TicketError::PayloadTooLarge`]。
+/// 用 [`TicketVerifier::verify_binary`] 验证。
+pub fn encode_binary(
+    version: TicketVersion,
+    payload: &[u8],
+    signature: &[u8; 64],
+) -> Result<Vec<u8>, TicketError> {
+    let len = u16::try_from(payload.len()).map_err(|_| TicketError::PayloadTooLarge)?;
+    let mut frame = Vec::with_capacity(3 + payload.len() + signature.len());
+    frame.push(version.binary_tag());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame.extend_from_slice(signature);
+    Ok(frame)
+}
+
+/// 解析 [`encode_binary`] 的二进制帧, 帧长度与声明不符或版本号未知时返回
+/// [`TicketError::MalformedStructure`]
+fn decode_binary(frame: &[u8]) -> Result<(TicketVersion, &[u8], &[u8]), TicketError> {
+    let [tag, len_hi, len_lo, rest @ ..] = frame else {
+        return Err(TicketError::MalformedStructure);
+    };
+    let version = TicketVersion::ALL
+        .into_iter()
+        .find(|v| v.binary_tag() == *tag)
+        .ok_or(TicketError::MalformedStructure)?;
+    let len = usize::from(u16::from_be_bytes([*len_hi, *len_lo]));
+    if rest.len() != len + 64 {
+        return Err(TicketError::MalformedStructure);
+    }
+    let (payload, signature) = rest.split_at(len);
+    Ok((version, payload, signature))
+}
+
+/// 只做结构解析和 base64 解码, 返回签名覆盖的原始字节和签名
+///
+/// 供外部审计工具用离线保存的候选公钥自行验签; 解析过程与
+/// [`TicketVerifier::verify`] 相同 (默认前缀, 默认载荷大小上限)。
+/// 压缩票据返回的是压缩后的字节, 即实际被签名的内容。
+/// 不验签, 返回的载荷字节**不可信**。
+pub fn split_ticket(ticket: &str) -> Result<(Vec<u8>, [u8; 64]), TicketError> {
+    let raw = decode_segments(
+        ticket.as_bytes(),
+        &[DEFAULT_PREFIX_BASE],
+        DEFAULT_MAX_PAYLOAD_BYTES,
+        Base64Variant::UrlSafeNoPad,
+    )?;
+    let signature: [u8; 64] = raw
+        .signature
+        .as_slice()
+        .try_into()
+        .map_err(|_| TicketError::BadSignatureLength(raw.signature.len()))?;
+    Ok((raw.payload_bytes, signature))
+}
+
+/// 去掉任一前缀, 返回票据版本和其后的内容
+fn split_version<'a>(
+    ticket: &'a [u8],
+    prefix_bases: &[impl AsRef<str>],
+) -> Option<(TicketVersion, &'a [u8])> {
+    prefix_bases.iter().find_map(|base| {
+        let rest = ticket.strip_prefix(base.as_ref().as_bytes())?;
+        TicketVersion::ALL
+            .into_iter()
+            .find_map(|v| rest.strip_prefix(v.marker().as_bytes()).map(|content| (v, content)))
+    })
+}
+
+/// 校验票据前缀格式, 返回版本标记之前的部分
+fn prefix_base(prefix: &str) -> Result<&str, TicketError> {
+    TicketVersion::ALL
+        .into_iter()
+        .find_map(|v| prefix.strip_suffix(v.marker()))
+        .filter(|base| !base.is_empty())
+        .ok_or_else(|| {
+            TicketError::ConfigurationError(format!(
+                "票据前缀必须以版本标记 (v1: 或 v2:) 结尾: {}",
+                prefix
+            ))
+        })
+}
+
+/// 不验签解析票据载荷
+///
+/// **结果不可信**: 不检查签名、有效期和目标设备, 任何人都能构造出任意内容。
+/// 仅用于调试和日志 (例如打印验证失败的票据声称的目标设备),
+/// 绝不能用于任何授权判断, 授权必须使用 [`TicketVerifier::verify`]。
+pub fn decode_payload_unverified(ticket: &str) -> Result<TicketPayload, TicketError> {
+    decode_unverified(
+        ticket.as_bytes(),
+        &[DEFAULT_PREFIX_BASE],
+        Base64Variant::UrlSafeNoPad,
+        DEFAULT_MAX_PAYLOAD_BYTES,
+    )
+}
+
+/// 不验签读取票据的签发时间、过期时间和剩余时间, 用于连接对话框显示
+/// "票据剩余有效期 4 分 12 秒"
+///
+/// **结果不可信**: 与 [`decode_payload_unverified`] 相同, 不需要公钥也不检查签名,
+/// 只能用于展示; 连接时仍由 [`TicketVerifier::verify`] 做授权判断。
+/// 输入不是可解析的票据时返回 None。
+pub fn ticket_time_info(ticket: &str) -> Option<TicketTimeInfo> {
+    let payload = decode_payload_unverified(ticket).ok()?;
+    let now = unix_time(SystemTime::now());
+    Some(TicketTimeInfo {
+        iat: payload.iat,
+        exp: payload.exp,
+        remaining: Duration::from_secs(payload.exp.saturating_sub(now).max(0) as u64),
+    })
+}
+
+/// 不验签读取票据的过期时间, 用于批量处理时在验签前丢弃明显已过期的票据
+///
+/// 只解析载荷中的 exp, 不构造完整的 [`TicketPayload`]。**结果不可信**: 不检查签名,
+/// 只能用于预筛选, 筛选后剩下的票据仍须经 [`TicketVerifier::verify`] 完整验证。
+/// 载荷段超过默认大小上限时不解码, 与输入不是可解析的票据或载荷中没有 exp 一样返回 None。
+pub fn peek_exp(ticket: &str) -> Option<i64> {
+    #[derive(Deserialize)]
+    struct ExpOnly {
+        exp: i64,
+    }
+
+    let (version, payload_bytes) = unverified_payload_bytes(
+        ticket.as_bytes(),
+        &[DEFAULT_PREFIX_BASE],
+        Base64Variant::UrlSafeNoPad,
+        DEFAULT_MAX_PAYLOAD_BYTES,
+    )
+    .ok()?;
+    let claims: ExpOnly = match version {
+        TicketVersion::V1 => serde_json::from_slice(&payload_bytes).ok()?,
+        TicketVersion::V2 => ciborium::from_reader(payload_bytes.as_slice()).ok()?,
+    };
+    Some(claims.exp)
+}
+
+fn decode_unverified(
+    ticket: &[u8],
+    prefix_bases: &[impl AsRef<str>],
+    base64: Base64Variant,
+    max_payload_bytes: usize,
+) -> Result<TicketPayload, TicketError> {
+    let (version, payload_bytes) =
+        unverified_payload_bytes(ticket, prefix_bases, base64, max_payload_bytes)?;
+    version.decode_payload(&payload_bytes)
+}
+
+/// 不验签取出载荷字节 (已解压), 解码前按 `max_payload_bytes` 检查载荷段长度
+fn unverified_payload_bytes(
+    ticket: &[u8],
+    prefix_bases: &[impl AsRef<str>],
+    base64: Base64Variant,
+    max_payload_bytes: usize,
+) -> Result<(TicketVersion, Vec<u8>), TicketError> {
+    let (version, payload_b64, _) = split_segments(ticket, prefix_bases)?;
+    if b64_decoded_len(payload_b64.len()) > max_payload_bytes {
+        return Err(TicketError::PayloadTooLarge);
+    }
+    let (compressed, payload_b64) = strip_compressed_marker(version, payload_b64);
+    let payload_bytes = base64.decode(payload_b64)?;
+    if compressed {
+        return Ok((version, inflate(&payload_bytes, max_payload_bytes)?));
+    }
+    Ok((version, payload_bytes))
+}
+
+/// 去掉 v2 载荷段的压缩标记, 返回是否压缩
+fn strip_compressed_marker(version: TicketVersion, payload_b64: &[u8]) -> (bool, &[u8]) {
+    match payload_b64.strip_prefix(&[COMPRESSED_MARKER as u8]) {
+        Some(rest) if version == TicketVersion::V2 => (true, rest),
+        _ => (false, payload_b64),
+    }
+}
+
+/// 解压 DEFLATE 载荷, 解压后超过 `limit` 字节时返回 [`TicketError::PayloadTooLarge`]
+fn inflate(compressed: &[u8], limit: usize) -> Result<Vec<u8>, TicketError> {
+    let mut out = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(TicketError::Decompress)?;
+    if out.len() > limit {
+        return Err(TicketError::PayloadTooLarge);
+    }
+    Ok(out)
+}
+
+fn parse_public_key_hex(hex_key: &str) -> Result<VerifyingKey, TicketError> {
+    let key_bytes = hex::decode(hex_key).map_err(TicketError::KeyHex)?;
+
+    if key_bytes.len() != 32 {
+        return Err(TicketError::BadKeyLength(key_bytes.len()));
+    }
+
+    let mut key_array = [0u8; 32];
+    key_array.copy_from_slice(&key_bytes);
+
+    VerifyingKey::from_bytes(&key_array).map_err(TicketError::InvalidKey)
+}
+
+/// 解析 JWKS 条目中 base64url 编码的 Ed25519 公钥
+fn parse_jwk_x(x: Option<&str>) -> Result<VerifyingKey, TicketError> {
+    let x = x.ok_or_else(|| TicketError::ConfigurationError("JWKS 条目缺少 x".to_owned()))?;
+    let key_bytes = URL_SAFE_NO_PAD
+        .decode(x.trim_end_matches('='))
+        .map_err(|e| TicketError::ConfigurationError(format!("JWKS 条目 x 解码失败: {}", e)))?;
+    let key_array: [u8; 32] = key_bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| TicketError::BadKeyLength(key_bytes.len()))?;
+    VerifyingKey::from_bytes(&key_array).map_err(TicketError::InvalidKey)
+}
+
+fn parse_public_key_pem(pem: &str) -> Result<VerifyingKey, TicketError> {
+    let body = pem
+        .trim()
+        .strip_prefix("-----BEGIN PUBLIC KEY-----")
+        .and_then(|s| s.strip_suffix("-----END PUBLIC KEY-----"))
+        .ok_or_else(|| TicketError::InvalidPem("缺少 PUBLIC KEY 头尾标记".to_owned()))?;
+    let body: String = body.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let der = STANDARD
+        .decode(body)
+        .map_err(|e| TicketError::InvalidPem(format!("base64 解码失败: {}", e)))?;
+    let key_bytes = der
+        .strip_prefix(&ED25519_SPKI_PREFIX[..])
+        .ok_or_else(|| TicketError::InvalidPem("不是 Ed25519 公钥".to_owned()))?;
+    let key_array: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|_| TicketError::BadKeyLength(key_bytes.len()))?;
+    VerifyingKey::from_bytes(&key_array).map_err(TicketError::InvalidKey)
+}
+
+/// 检查密码是否应按票据处理 (默认前缀), 判断规则与 [`authenticate_password`] 相同
+///
+/// 以 `TICKET:` 开头即返回 true, 包括版本标记未知或格式错误的输入: 这些输入会作为
+/// 无效票据被拒绝, 不会交给普通密码验证。
+pub fn is_ticket(password: &[u8]) -> bool {
+    !password.iter().all(u8::is_ascii_whitespace)
+        && presents_as_ticket(password, &[DEFAULT_PREFIX_BASE])
+}
+
+/// 认证连接密码时是否按票据处理, [`authenticate_password`] 与 [`authenticate`] 共用
+///
+/// 以任一前缀的版本标记之前部分 (默认 `TICKET:`) 开头即按票据处理, 版本标记未知
+/// (如 `TICKET:v3:`) 或其余部分格式错误的同样按票据验证并拒绝, 不会交给普通密码验证。
+fn presents_as_ticket(password: &[u8], prefix_bases: &[impl AsRef<str>]) -> bool {
+    prefix_bases
+        .iter()
+        .any(|base| password.starts_with(base.as_ref().as_bytes()))
+}
+
+/// 连接密码的认证结果
+// 每次连接认证只构造一次, 不值得为变体大小差异装箱
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum AuthResult {
+    /// 票据验证通过
+    Ticket(TicketPayload),
+    /// 不是票据, 调用方应继续普通密码验证
+    NotTicket,
+    /// 是票据但验证失败, 调用方应直接拒绝连接, 不能再回退到普通密码验证
+    TicketInvalid(TicketError),
+}
+
+/// 认证连接握手中收到的密码
+///
+/// 连接处理流程应先调用该函数, 只有返回 [`AuthResult::NotTicket`] 时才继续普通密码验证。
+///
+/// # 参数
+/// - `password`: 密码字节数组 (可能是票据)
+/// - `my_device_id`: 本机设备 ID, 为空 (如尚未分配 ID) 时票据一律验证失败,
+///   返回 [`TicketError::ConfigurationError`], 不会跳过目标设备校验
+/// - `public_key_hex`: API Server 的公钥 (十六进制), 为空时票据一律验证失败
+pub fn authenticate_password(password: &[u8], my_device_id: &str, public_key_hex: &str) -> AuthResult {
+    authenticate_with_binding(password, my_device_id, public_key_hex, None)
+}
+
+/// [`authenticate_password`] 的实现, `binding` 为 None 时按 `my_device_id` 精确匹配
+fn authenticate_with_binding(
+    password: &[u8],
+    my_device_id: &str,
+    public_key_hex: &str,
+    binding: Option<DeviceBinding>,
+) -> AuthResult {
+    // 空密码 (含只有空白) 不是票据, 交给普通密码验证处理
+    if !is_ticket(password) {
+        return AuthResult::NotTicket;
+    }
+    let ticket_str = match std::str::from_utf8(password) {
+        Ok(s) => s,
+        Err(_) => return AuthResult::TicketInvalid(TicketError::MalformedStructure),
+    };
+
+    if public_key_hex.is_empty() {
+        log::warn!(target: LOG_TARGET, "收到免密连接票据但未配置 ticket-public-key");
+        return AuthResult::TicketInvalid(TicketError::MissingPublicKey);
+    }
+    let mut verifier = TicketVerifier::new();
+    if let Err(e) = verifier.set_public_key_hex(public_key_hex) {
+        log::warn!(target: LOG_TARGET, "设置票据公钥失败: {}", e);
+        return AuthResult::TicketInvalid(e);
+    }
+    match binding {
+        Some(binding) => verifier.with_device_binding(binding),
+        None if my_device_id.is_empty() => {
+            log::warn!(target: LOG_TARGET, "收到免密连接票据但本机设备 ID 为空");
+            return AuthResult::TicketInvalid(TicketError::ConfigurationError(
+                "本机设备 ID 为空, 无法校验票据目标设备".to_owned(),
+            ));
+        }
+        None => {}
+    }
+
+    // 验证结果由验证器记录日志
+    match verifier.verify(ticket_str, my_device_id) {
+        Ok(payload) => AuthResult::Ticket(payload),
+        Err(e) => AuthResult::TicketInvalid(e),
+    }
+}
+
+/// [`authenticate`] 的认证结论
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum AuthDecision {
+    /// 票据验证通过
+    Ticket(TicketPayload),
+    /// 不是票据, 普通密码验证通过
+    Password,
+    /// 拒绝连接: 票据验证失败时附带原因, 普通密码错误时为 None
+    Denied(Option<TicketError>),
+}
+
+/// 按既定优先级认证连接密码: 先票据, 后普通密码
+///
+/// 以 `verifier` 任一前缀开头的输入都按票据处理 (其余部分格式错误也一样, 规则与
+/// [`authenticate_password`] 相同), 验证失败时直接拒绝, 不会再交给 `password_check`,
+/// 避免畸形票据碰巧等于共享密码时被放行; 其它输入交给 `password_check` 判断。
+pub fn authenticate(
+    password: &[u8],
+    device_id: &str,
+    verifier: &TicketVerifier,
+    password_check: impl Fn(&[u8]) -> bool,
+) -> AuthDecision {
+    if !presents_as_ticket(password, &verifier.config.prefix_bases) {
+        return if password_check(password) {
+            AuthDecision::Password
+        } else {
+            AuthDecision::Denied(None)
+        };
+    }
+    let Ok(ticket) = std::str::from_utf8(password) else {
+        return AuthDecision::Denied(Some(TicketError::MalformedStructure));
+    };
+    match verifier.verify(ticket, device_id) {
+        Ok(payload) => AuthDecision::Ticket(payload),
+        Err(e) => AuthDecision::Denied(Some(e)),
+    }
+}
+
+/// [`classify_and_verify`] 的结果
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum TicketOutcome {
+    /// 不是票据, 调用方应继续普通密码验证
+    NotTicket,
+    /// 票据验证通过
+    Valid(TicketPayload),
+    /// 是票据但验证失败, 调用方应直接拒绝连接
+    Invalid(TicketError),
+}
+
+impl From<AuthResult> for TicketOutcome {
+    fn from(result: AuthResult) -> Self {
+        match result {
+            AuthResult::NotTicket => TicketOutcome::NotTicket,
+            AuthResult::Ticket(payload) => TicketOutcome::Valid(payload),
+            AuthResult::TicketInvalid(e) => TicketOutcome::Invalid(e),
+        }
+    }
+}
+
+/// 区分"不是票据"和"票据无效"的验证入口, 判断规则与 [`authenticate_password`] 相同
+///
+/// 只有返回 [`TicketOutcome::NotTicket`] 时才应回退到普通密码验证;
+/// 票据无效的情况可以单独记录日志和统计。
+pub fn classify_and_verify(password: &[u8], my_device_id: &str, public_key_hex: &str) -> TicketOutcome {
+    authenticate_password(password, my_device_id, public_key_hex).into()
+}
+
+/// 尝试验证票据
+/// 
+/// # 参数
+/// - `password`: 密码字节数组 (可能是票据)
+/// - `my_device_id`: 本机设备 ID; 兼容旧调用方, 为空时不校验 dst_id 并记录警告
+/// - `public_key_hex`: API Server 的公钥 (十六进制)
+/// 
+/// # 返回
+/// - `Some(TicketPayload)`: 验证成功
+/// - `None`: 不是票据或验证失败 (需要区分两者时使用 [`classify_and_verify`])
+pub fn try_verify_ticket(password: &[u8], my_device_id: &str, public_key_hex: &str) -> Option<TicketPayload> {
+    // 兼容旧调用方: 空设备 ID 表示不校验 dst_id
+    let binding = if my_device_id.is_empty() {
+        log::warn!(target: LOG_TARGET, "票据验证未提供本机设备 ID, 跳过目标设备校验");
+        Some(DeviceBinding::AnyDevice)
+    } else {
+        None
+    };
+    match authenticate_with_binding(password, my_device_id, public_key_hex, binding) {
+        AuthResult::Ticket(payload) => Some(payload),
+        AuthResult::NotTicket | AuthResult::TicketInvalid(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_payload(dst_id: &str) -> TicketPayload {
+        let now = unix_time(SystemTime::now());
+        TicketPayload {
+            src_id: "123456789".to_owned(),
+            dst_id: dst_id.into(),
+            exp: now + 60,
+            nonce: "0123456789abcdef".to_owned(),
+            iat: now,
+            ..Default::default()
+        }
+    }
+
+    struct FixedClock(i64);
+
+    impl Clock for FixedClock {
+        fn now_unix(&self) -> i64 {
+            self.0
+        }
+    }
+
+    fn test_signer(seed: u8) -> TicketSigner {
+        TicketSigner::from_secret_hex(&hex::encode([seed; 32])).unwrap()
+    }
+
+    fn test_verifier(signer: &TicketSigner) -> TicketVerifier {
+        let mut verifier = TicketVerifier::new();
+        verifier
+            .set_public_key_hex(&signer.verifying_key_hex())
+            .unwrap();
+        verifier
+    }
+
+    #[test]
+    fn test_is_ticket() {
+        assert!(is_ticket(b"TICKET:v1:abc.def"));
+        assert!(is_ticket(b"TICKET:v2:abc.def"));
+        // 未知版本和畸形输入同样按票据处理, 由验证拒绝
+        assert!(is_ticket(b"TICKET:v3:abc.def"));
+        assert!(is_ticket(b"TICKET:garbage"));
+        assert!(!is_ticket(b"password123"));
+        assert!(!is_ticket(b""));
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let mut verifier = TicketVerifier::new();
+        assert!(matches!(
+            verifier.verify("TICKET:v1:abc.def", ""),
+            Err(TicketError::MissingPublicKey)
+        ));
+        assert!(matches!(
+            verifier.set_public_key_hex("zz"),
+            Err(TicketError::KeyHex(_))
+        ));
+        assert!(matches!(
+            verifier.set_public_key_hex("00"),
+            Err(TicketError::BadKeyLength(1))
+        ));
+        verifier.set_public_key_hex(&"00".repeat(32)).unwrap();
+        assert!(matches!(
+            verifier.verify("password123", ""),
+            Err(TicketError::BadPrefix)
+        ));
+        assert!(matches!(
+            verifier.verify("TICKET:v1:abc", ""),
+            Err(TicketError::MissingSeparator)
+        ));
+        assert!(matches!(
+            verifier.verify("TICKET:v1:a.def", ""),
+            Err(TicketError::Base64(_))
+        ));
+        assert_eq!(TicketError::Expired.to_string(), "票据已过期");
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let code = |ticket: &str, device_id: &str| {
+            verifier.verify(ticket, device_id).unwrap_err().code()
+        };
+
+        let mut expired = test_payload("987654321");
+        expired.iat -= 7200;
+        expired.exp = expired.iat + 60;
+        assert_eq!(code(&signer.sign(&expired), "987654321"), "EXPIRED");
+        let ticket = signer.sign(&test_payload("987654321"));
+        assert_eq!(code(&ticket, "111111111"), "DEVICE_MISMATCH");
+        assert_eq!(
+            code(&test_signer(2).sign(&test_payload("987654321")), "987654321"),
+            "SIG_INVALID"
+        );
+        assert_eq!(code("TICKET:v1:abc", "987654321"), "MALFORMED");
+        assert_eq!(
+            TicketVerifier::new().verify(&ticket, "987654321").unwrap_err().code(),
+            "NO_KEY"
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old_key = test_signer(1);
+        let new_key = test_signer(2);
+        let other_key = test_signer(3);
+        let mut verifier = test_verifier(&old_key);
+        verifier
+            .add_public_key_hex(&new_key.verifying_key_hex())
+            .unwrap();
+
+        let payload = test_payload("987654321");
+        assert!(verifier.verify(&old_key.sign(&payload), "987654321").is_ok());
+        assert!(verifier.verify(&new_key.sign(&payload), "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&other_key.sign(&payload), "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+
+        // set_public_key_hex 替换全部公钥
+        verifier
+            .set_public_key_hex(&new_key.verifying_key_hex())
+            .unwrap();
+        assert!(matches!(
+            verifier.verify(&old_key.sign(&payload), "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_key_sunset() {
+        let old = test_signer(1);
+        let new = test_signer(2);
+        let mut verifier = test_verifier(&new);
+        verifier.with_clock(Box::new(FixedClock(1_000)));
+        verifier
+            .add_public_key_hex_until(None, &old.verifying_key_hex(), 1_100)
+            .unwrap();
+        verifier
+            .add_public_key_hex_until(Some("old"), &old.verifying_key_hex(), 1_100)
+            .unwrap();
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 2_000;
+        let ticket = old.sign(&payload);
+        payload.kid = Some("old".to_owned());
+        let kid_ticket = old.sign(&payload);
+
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        assert!(verifier.verify(&kid_ticket, "987654321").is_ok());
+        assert!(verifier.verify_at(&ticket, "987654321", 1_100).is_ok());
+        assert_eq!(verifier.prune_expired_keys(), 0);
+
+        verifier.with_clock(Box::new(FixedClock(1_101)));
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+        assert!(matches!(
+            verifier.verify(&kid_ticket, "987654321"),
+            Err(TicketError::UnknownKeyId(_))
+        ));
+        payload.kid = None;
+        assert!(verifier.verify(&new.sign(&payload), "987654321").is_ok());
+        assert_eq!(verifier.prune_expired_keys(), 2);
+        assert_eq!(verifier.config.public_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_key_id_selection() {
+        let key1 = test_signer(1);
+        let key2 = test_signer(2);
+        let mut verifier = TicketVerifier::new();
+        verifier
+            .add_public_key_hex_with_id("k1", &key1.verifying_key_hex())
+            .unwrap();
+        verifier
+            .add_public_key_hex_with_id("k2", &key2.verifying_key_hex())
+            .unwrap();
+
+        let mut payload = test_payload("987654321");
+        payload.kid = Some("k2".to_owned());
+        assert!(verifier.verify(&key2.sign(&payload), "987654321").is_ok());
+        // kid 指向 k2 但由 k1 签名: 不回退尝试其他公钥
+        assert!(matches!(
+            verifier.verify(&key1.sign(&payload), "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+
+        payload.kid = Some("k3".to_owned());
+        assert!(matches!(
+            verifier.verify(&key1.sign(&payload), "987654321"),
+            Err(TicketError::UnknownKeyId(kid)) if kid == "k3"
+        ));
+
+        // 无 kid 的票据尝试全部公钥
+        payload.kid = None;
+        assert!(verifier.verify(&key1.sign(&payload), "987654321").is_ok());
+    }
+
+    #[test]
+    fn test_replay_protection() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_replay_cache(Arc::new(ReplayCache::new()));
+
+        let mut payload = test_payload("987654321");
+        let ticket = signer.sign(&payload);
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::ReplayDetected)
+        ));
+
+        payload.nonce = "fedcba9876543210".to_owned();
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+    }
+
+    #[test]
+    fn test_decode_payload_unverified() {
+        let payload = test_payload("987654321");
+        let ticket = test_signer(1).sign(&payload);
+        // 不需要公钥, 也不检查签名
+        let decoded = decode_payload_unverified(&ticket).unwrap();
+        assert_eq!(decoded.src_id, payload.src_id);
+        assert_eq!(decoded.dst_id, payload.dst_id);
+        assert_eq!(decoded.exp, payload.exp);
+        let (body, _) = ticket.split_at(ticket.rfind('.').unwrap());
+        assert!(decode_payload_unverified(&format!("{}.AAAA", body)).is_ok());
+
+        assert!(matches!(
+            decode_payload_unverified("password123"),
+            Err(TicketError::BadPrefix)
+        ));
+        assert!(matches!(
+            decode_payload_unverified("TICKET:v1:e30.AAAA"),
+            Err(TicketError::PayloadParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.exp = payload.iat - 10;
+        payload.iat -= 60;
+        let ticket = signer.sign(&payload);
+        // 默认允许 30 秒偏差
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier.with_clock_skew(0).unwrap();
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::Expired)
+        ));
+        assert!(matches!(
+            verifier.with_clock_skew(-1),
+            Err(TicketError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_not_before() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+
+        // 恰好位于偏差边界时可用
+        payload.nbf = Some(payload.iat + DEFAULT_CLOCK_SKEW_SECS);
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+
+        payload.nbf = Some(payload.iat + DEFAULT_CLOCK_SKEW_SECS + 5);
+        payload.exp = payload.iat + 120;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::NotYetValid)
+        ));
+
+        // 不带 nbf 字段的旧票据照常解析
+        let legacy: TicketPayload = serde_json::from_str(
+            r#"{"src_id":"1","dst_id":"2","exp":0,"nonce":"n","iat":0}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.nbf, None);
+    }
+
+    #[test]
+    fn test_issued_in_future() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.iat += 20;
+        payload.exp = payload.iat + 60;
+        let ticket = signer.sign(&payload);
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier.with_clock_skew(5).unwrap();
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::IssuedInFuture)
+        ));
+    }
+
+    #[test]
+    fn test_iat_window() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_clock(Box::new(FixedClock(1_000)));
+        verifier.with_iat_window(120).unwrap();
+        let ticket = |iat: i64| {
+            let mut payload = test_payload("987654321");
+            payload.iat = iat;
+            payload.exp = iat + 600;
+            signer.sign(&payload)
+        };
+
+        assert!(verifier.verify(&ticket(880), "987654321").is_ok());
+        assert!(verifier.verify(&ticket(1_120), "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&ticket(879), "987654321"),
+            Err(TicketError::IssuedTooFarFromNow)
+        ));
+        // 窗口取代 IssuedInFuture 检查, 不叠加时钟偏差
+        assert!(matches!(
+            verifier.verify(&ticket(1_121), "987654321"),
+            Err(TicketError::IssuedTooFarFromNow)
+        ));
+        // 过期优先报告
+        assert!(matches!(
+            verifier.verify(&ticket(0), "987654321"),
+            Err(TicketError::Expired)
+        ));
+        assert!(verifier.with_iat_window(-1).is_err());
+    }
+
+    #[test]
+    fn test_timestamps_inconsistent() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.exp = payload.iat - 1;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::TimestampsInconsistent)
+        ));
+        // exp == iat 合法 (此处已过期, 按过期处理)
+        payload.iat -= 3600;
+        payload.exp = payload.iat;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_max_lifetime() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_max_lifetime(600).unwrap();
+        let mut payload = test_payload("987654321");
+        payload.exp = payload.iat + 600;
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        payload.exp = payload.iat + 601;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::LifetimeTooLong)
+        ));
+        assert!(verifier.with_max_lifetime(0).is_err());
+    }
+
+    #[test]
+    fn test_max_absolute_exp() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_clock(Box::new(FixedClock(1_000)));
+        verifier.with_max_absolute_exp(3_600).unwrap();
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 4_600;
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        payload.exp = 4_601;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::ExpTooFar)
+        ));
+        payload.exp = 253_402_300_799;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::ExpTooFar)
+        ));
+        assert!(verifier.with_max_absolute_exp(0).is_err());
+    }
+
+    #[test]
+    fn test_clock_before_epoch() {
+        assert_eq!(unix_time(UNIX_EPOCH - Duration::from_secs(10)), -10);
+        assert_eq!(unix_time(UNIX_EPOCH - Duration::from_millis(500)), -1);
+        assert_eq!(unix_time(UNIX_EPOCH + Duration::from_secs(10)), 10);
+
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let now = unix_time(UNIX_EPOCH - Duration::from_secs(3600));
+        assert!(matches!(
+            verifier.verify_at(&ticket, "987654321", now),
+            Err(TicketError::ClockError)
+        ));
+    }
+
+    #[test]
+    fn test_injected_clock() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let payload = TicketPayload {
+            src_id: "123456789".to_owned(),
+            dst_id: "987654321".into(),
+            exp: 1_000_060,
+            nonce: "0123456789abcdef".to_owned(),
+            iat: 1_000_000,
+            nbf: Some(1_000_010),
+            ..Default::default()
+        };
+        let ticket = signer.sign(&payload);
+        let skew = DEFAULT_CLOCK_SKEW_SECS;
+
+        verifier.with_clock(Box::new(FixedClock(1_000_060 + skew)));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier.with_clock(Box::new(FixedClock(1_000_060 + skew + 1)));
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::Expired)
+        ));
+
+        verifier.with_clock(Box::new(FixedClock(1_000_010 - skew)));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier.with_clock(Box::new(FixedClock(1_000_010 - skew - 1)));
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::NotYetValid)
+        ));
+
+        verifier.with_clock(Box::new(FixedClock(-1)));
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::ClockError)
+        ));
+    }
+
+    #[test]
+    fn test_expected_audience() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        // 未配置受众时忽略 aud
+        payload.aud = Some("file-service".into());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+
+        verifier.with_expected_audience("rustdesk");
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::AudienceMismatch)
+        ));
+        payload.aud = Some("rustdesk".into());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        payload.aud = None;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::AudienceMismatch)
+        ));
+
+        // 多个受众: 包含期望值即通过, v1 和 v2 编码一致
+        payload.aud = Some(vec!["file-service".to_owned(), "rustdesk".to_owned()].into());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        assert!(verifier.verify(&signer.sign_v2(&payload), "987654321").is_ok());
+        payload.aud = Some(vec!["file-service".to_owned(), "rustdesk-beta".to_owned()].into());
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::AudienceMismatch)
+        ));
+
+        let aud: Audience = serde_json::from_str(r#"["a","b"]"#).unwrap();
+        assert!(aud.contains("b") && !aud.contains("c"));
+        assert_eq!(serde_json::from_str::<Audience>(r#""a""#).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_expected_type() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.typ = Some("provisioning".to_owned());
+        // 未配置类型时忽略 typ
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+
+        verifier.with_expected_type("connect");
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::WrongTicketType)
+        ));
+        payload.typ = Some("connect".to_owned());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        payload.typ = None;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::WrongTicketType)
+        ));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let mut payload = test_payload("987654321");
+        assert!(!payload.has_scope(SCOPE_CONTROL));
+        payload.scopes = vec![SCOPE_VIEW.to_owned(), SCOPE_FILE.to_owned()];
+        assert!(payload.has_scope(SCOPE_VIEW));
+        assert!(payload.has_scope(SCOPE_FILE));
+        assert!(!payload.has_scope(SCOPE_CONTROL));
+        // 不做子串匹配
+        assert!(!payload.has_scope("vie"));
+        assert!(!payload.has_scope("file-write"));
+        assert!(!payload.has_scope(""));
+
+        let signer = test_signer(1);
+        let verified = test_verifier(&signer)
+            .verify(&signer.sign(&payload), "987654321")
+            .unwrap();
+        assert_eq!(verified.scopes, payload.scopes);
+    }
+
+    #[test]
+    fn test_revoked_ticket() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let list = RevocationList::new();
+        verifier.with_revocation_list(list.clone());
+
+        let ticket = signer.sign(&test_payload("987654321"));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        list.revoke_src("123456789");
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::Revoked)
+        ));
+    }
+
+    #[test]
+    fn test_verify_cache() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_verify_cache(16);
+        let mut payload = test_payload("987654321");
+        payload.exp = 1_000_060;
+        payload.iat = 1_000_000;
+        let ticket = signer.sign(&payload);
+
+        verifier.with_clock(Box::new(FixedClock(1_000_000)));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        // 命中缓存时仍检查设备和有效期
+        assert!(matches!(
+            verifier.verify(&ticket, "111111111"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+        verifier.with_clock(Box::new(FixedClock(1_000_100)));
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::Expired)
+        ));
+
+        // 更换公钥后缓存失效
+        verifier.with_clock(Box::new(FixedClock(1_000_000)));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier
+            .set_public_key_hex(&test_signer(2).verifying_key_hex())
+            .unwrap();
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_v2_cbor_round_trip() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.scopes = vec![SCOPE_VIEW.to_owned()];
+
+        let v1 = signer.sign(&payload);
+        let v2 = signer.sign_v2(&payload);
+        assert!(v2.starts_with(TICKET_PREFIX_V2));
+        assert!(v2.len() < v1.len());
+
+        for ticket in [&v1, &v2] {
+            let verified = verifier.verify(ticket, "987654321").unwrap();
+            assert_eq!(verified.src_id, payload.src_id);
+            assert_eq!(verified.exp, payload.exp);
+            assert_eq!(verified.scopes, payload.scopes);
+        }
+        assert_eq!(decode_payload_unverified(&v2).unwrap().dst_id, "987654321");
+
+        // v2 前缀搭配 JSON 载荷无法解析
+        let mislabeled = v1.replacen(TICKET_PREFIX, TICKET_PREFIX_V2, 1);
+        assert!(verifier.verify(&mislabeled, "987654321").is_err());
+    }
+
+    #[test]
+    fn test_ed25519ph() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let payload = test_payload("987654321");
+
+        let prehashed = signer.sign_v2_prehashed(&payload);
+        let verified = verifier.verify(&prehashed, "987654321").unwrap();
+        assert_eq!(verified.alg.as_deref(), Some(ALG_ED25519PH));
+        assert!(verifier.verify(&signer.sign_v2(&payload), "987654321").is_ok());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+
+        // 算法标记与签名方式不一致时验签失败
+        let mut mislabeled = payload.clone();
+        mislabeled.alg = Some(ALG_ED25519PH.to_owned());
+        assert!(matches!(
+            verifier.verify(&signer.sign_v2(&mislabeled), "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+        assert!(matches!(
+            test_verifier(&test_signer(2)).verify(&prehashed, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_v2_compressed() {
+        use ed25519_dalek::Signer;
+        use flate2::{write::DeflateEncoder, Compression};
+        use std::io::Write;
+
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.scopes = (0..40).map(|i| format!("scope-{}", i)).collect();
+
+        let plain = signer.sign_v2(&payload);
+        let compressed = signer.sign_v2_compressed(&payload);
+        assert!(compressed.starts_with("TICKET:v2:~"));
+        assert!(compressed.len() < plain.len());
+        for ticket in [&plain, &compressed] {
+            let verified = verifier.verify(ticket, "987654321").unwrap();
+            assert_eq!(verified.scopes, payload.scopes);
+        }
+        assert_eq!(
+            decode_payload_unverified(&compressed).unwrap().src_id,
+            payload.src_id
+        );
+
+        // 压缩标记只对 v2 有效
+        let v1 = compressed.replacen(TICKET_PREFIX_V2, TICKET_PREFIX, 1);
+        assert!(matches!(
+            verifier.verify(&v1, "987654321"),
+            Err(TicketError::Base64(_))
+        ));
+
+        // 篡改压缩字节后验签失败, 不会尝试解压
+        let (segment, signature_b64) = compressed
+            .strip_prefix("TICKET:v2:~")
+            .and_then(|rest| rest.split_once('.'))
+            .unwrap();
+        let mut bytes = Base64Variant::UrlSafeNoPad.decode(segment.as_bytes()).unwrap();
+        bytes[0] ^= 1;
+        let tampered = format!("TICKET:v2:~{}.{}", b64_encode(&bytes), signature_b64);
+        assert!(matches!(
+            verifier.verify(&tampered, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+
+        // 解压后超过上限的载荷 (解压炸弹) 被拒绝
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&[0u8; 1024 * 1024]).unwrap();
+        let bomb = encoder.finish().unwrap();
+        let signature = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]).sign(&bomb);
+        let ticket = format!(
+            "{}~{}.{}",
+            TICKET_PREFIX_V2,
+            b64_encode(&bomb),
+            b64_encode(&signature.to_bytes())
+        );
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::PayloadTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_b64_codec() {
+        let decode = |segment: &str| Base64Variant::UrlSafeNoPad.decode(segment.as_bytes());
+        assert_eq!(decode("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(b64_encode(b"hello"), "aGVsbG8");
+        assert_eq!(decode("-_8").unwrap(), [0xfb, 0xff]);
+        // 拒绝填充和标准字母表
+        assert!(matches!(decode("aGVsbG8="), Err(TicketError::Base64(_))));
+        assert!(matches!(decode("+/8"), Err(TicketError::Base64(_))));
+    }
+
+    #[test]
+    fn test_split_ticket() {
+        use ed25519_dalek::{Signature, Verifier};
+
+        let signer = test_signer(1);
+        let payload = test_payload("987654321");
+        let key = parse_public_key_hex(&signer.verifying_key_hex()).unwrap();
+        for ticket in [
+            signer.sign(&payload),
+            signer.sign_v2(&payload),
+            signer.sign_v2_compressed(&payload),
+        ] {
+            let (bytes, signature) = split_ticket(&ticket).unwrap();
+            assert!(key.verify(&bytes, &Signature::from_bytes(&signature)).is_ok());
+        }
+
+        let (bytes, _) = split_ticket(&signer.sign(&payload)).unwrap();
+        assert_eq!(bytes, canonical_json(&payload));
+        assert!(matches!(
+            split_ticket("TICKET:v1:abc.AAAA"),
+            Err(TicketError::BadSignatureLength(3))
+        ));
+        assert!(matches!(split_ticket("hunter2"), Err(TicketError::BadPrefix)));
+    }
+
+    #[test]
+    fn test_segment_separator() {
+        assert!(matches!(
+            split_segments(b"TICKET:v1:abc", &[DEFAULT_PREFIX_BASE]),
+            Err(TicketError::MissingSeparator)
+        ));
+        assert!(matches!(
+            split_segments(b"TICKET:v1:abc.def", &[DEFAULT_PREFIX_BASE]),
+            Ok((TicketVersion::V1, b"abc", b"def"))
+        ));
+        assert!(matches!(
+            split_segments(b"TICKET:v1:abc.def.ghi", &[DEFAULT_PREFIX_BASE]),
+            Err(TicketError::MalformedStructure)
+        ));
+        assert!(matches!(
+            split_segments(b"TICKET:v1:..", &[DEFAULT_PREFIX_BASE]),
+            Err(TicketError::MalformedStructure)
+        ));
+    }
+
+    #[test]
+    fn test_max_payload_bytes() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.scopes = vec!["x".repeat(1000)];
+        let ticket = signer.sign(&payload);
+        let payload_len = serde_json::to_vec(&payload).unwrap().len();
+
+        verifier.with_max_payload_bytes(payload_len).unwrap();
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier.with_max_payload_bytes(payload_len - 1).unwrap();
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::PayloadTooLarge)
+        ));
+        // 日志和回调也不解码超出上限的载荷
+        assert!(verifier.claims_unverified(ticket.as_bytes()).is_none());
+
+        // 超大输入在解码前被拒绝 (否则会报 base64 错误)
+        let verifier = test_verifier(&signer);
+        let huge = format!("{}{}.AAAA", TICKET_PREFIX, "!".repeat(4 * 1024 * 1024));
+        assert!(matches!(
+            verifier.verify(&huge, "987654321"),
+            Err(TicketError::PayloadTooLarge)
+        ));
+        assert!(TicketVerifier::new().with_max_payload_bytes(0).is_err());
+    }
+
+    #[test]
+    fn test_base64_variant() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let (head, signature_b64) = ticket.rsplit_once('.').unwrap();
+        let payload_b64 = head.strip_prefix(TICKET_PREFIX).unwrap();
+        let payload_bytes = Base64Variant::UrlSafeNoPad.decode(payload_b64.as_bytes()).unwrap();
+        let signature = Base64Variant::UrlSafeNoPad.decode(signature_b64.as_bytes()).unwrap();
+
+        for variant in [
+            Base64Variant::UrlSafeNoPad,
+            Base64Variant::UrlSafe,
+            Base64Variant::Standard,
+            Base64Variant::StandardNoPad,
+        ] {
+            // 同一载荷和签名, 只换传输编码
+            let engine = variant.engine();
+            let encoded = format!(
+                "{}{}.{}",
+                TICKET_PREFIX,
+                engine.encode(&payload_bytes),
+                engine.encode(&signature)
+            );
+            verifier.with_base64_variant(variant);
+            let payload = verifier.verify(&encoded, "987654321").unwrap();
+            assert_eq!(payload.src_id, "123456789", "{:?}", variant);
+        }
+
+        // 标准字母表带填充的签名段不能按默认方式解码
+        let standard = format!(
+            "{}{}.{}",
+            TICKET_PREFIX,
+            STANDARD.encode(&payload_bytes),
+            STANDARD.encode(&signature)
+        );
+        verifier.with_base64_variant(Base64Variant::UrlSafeNoPad);
+        assert!(matches!(
+            verifier.verify(&standard, "987654321"),
+            Err(TicketError::Base64(_))
+        ));
+    }
+
+    #[test]
+    fn test_signature_segment_length() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let (head, signature_b64) = ticket.rsplit_once('.').unwrap();
+        assert_eq!(signature_b64.len(), 86);
+
+        // 超长的签名段在解码前被拒绝 (否则会报 base64 错误)
+        let long = format!("{}.{}", head, "!".repeat(MAX_SIGNATURE_B64_LEN + 1));
+        assert!(matches!(
+            verifier.verify(&long, "987654321"),
+            Err(TicketError::MalformedStructure)
+        ));
+        let padded = format!("{}.{}", head, "A".repeat(MAX_SIGNATURE_B64_LEN));
+        assert!(matches!(
+            verifier.verify(&padded, "987654321"),
+            Err(TicketError::BadSignatureLength(768))
+        ));
+    }
+
+    #[test]
+    fn test_device_binding() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+
+        // 空设备 ID 不再隐式跳过检查
+        assert!(matches!(
+            verifier.verify(&ticket, ""),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+        verifier.with_device_binding(DeviceBinding::AnyDevice);
+        assert!(verifier.verify(&ticket, "").is_ok());
+        verifier.with_device_binding(DeviceBinding::Exact("111111111".to_owned()));
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::DeviceMismatch { expected, .. }) if expected == "111111111"
+        ));
+
+        // 只有 try_verify_ticket 保留空字符串语义
+        let public_key = signer.verifying_key_hex();
+        assert!(try_verify_ticket(ticket.as_bytes(), "", &public_key).is_some());
+        assert!(matches!(
+            classify_and_verify(ticket.as_bytes(), "", &public_key),
+            TicketOutcome::Invalid(TicketError::ConfigurationError(_))
+        ));
+        assert!(try_verify_ticket(ticket.as_bytes(), "111111111", &public_key).is_none());
+    }
+
+    #[test]
+    fn test_public_key_pem() {
+        // openssl genpkey -algorithm ed25519 | openssl pkey -pubout
+        let pem = "-----BEGIN PUBLIC KEY-----\n\
+                   MCowBQYDK2VwAyEAeCyDJERpcU9E5gFqbTV0lDJIhgtqE910jys8ILjANe4=\n\
+                   -----END PUBLIC KEY-----\n";
+        assert_eq!(
+            hex::encode(parse_public_key_pem(pem).unwrap().as_bytes()),
+            "782c83244469714f44e6016a6d3574943248860b6a13dd748f2b3c20b8c035ee"
+        );
+
+        let signer = test_signer(1);
+        let spki = [&ED25519_SPKI_PREFIX[..], &hex::decode(signer.verifying_key_hex()).unwrap()]
+            .concat();
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----",
+            STANDARD.encode(spki)
+        );
+        let mut verifier = TicketVerifier::new();
+        verifier.set_public_key_pem(&pem).unwrap();
+        assert!(verifier
+            .verify(&signer.sign(&test_payload("987654321")), "987654321")
+            .is_ok());
+
+        // P-256 公钥
+        let p256 = "-----BEGIN PUBLIC KEY-----\n\
+                    MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEwdP/ngZNtsi5aPM7jeUSEeOMTyfw\n\
+                    x5M1lzdirSxygxn8mXFEFd91H903WVmA1wIMmm5MtFSEidzozphbkbMg8A==\n\
+                    -----END PUBLIC KEY-----";
+        assert!(matches!(
+            verifier.set_public_key_pem(p256),
+            Err(TicketError::InvalidPem(_))
+        ));
+        assert!(matches!(
+            verifier.set_public_key_pem("MCowBQYDK2VwAyEA"),
+            Err(TicketError::InvalidPem(_))
+        ));
+        assert!(matches!(
+            verifier.set_public_key_pem(
+                "-----BEGIN PUBLIC KEY-----\n!!!\n-----END PUBLIC KEY-----"
+            ),
+            Err(TicketError::InvalidPem(_))
+        ));
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq("987654321", "987654321"));
+        assert!(ct_eq("", ""));
+        assert!(!ct_eq("987654321", "987654320"));
+        assert!(!ct_eq("987654321", "98765432"));
+        assert!(!ct_eq("", "987654321"));
+    }
+
+    #[test]
+    fn test_shared_verifier() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<TicketVerifier>();
+
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_verify_cache(16);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let verifier = verifier.clone();
+                let ticket = ticket.clone();
+                std::thread::spawn(move || verifier.verify(&ticket, "987654321").is_ok())
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+
+        // 修改配置不影响已分发的句柄, 也不会复用旧公钥的验签缓存
+        let shared = verifier.clone();
+        verifier
+            .set_public_key_hex(&test_signer(2).verifying_key_hex())
+            .unwrap();
+        assert!(shared.verify(&ticket, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_on_result_hook() {
+        use std::sync::Mutex;
+
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        verifier.with_on_result(Box::new(move |outcome| {
+            sink.lock().unwrap().push((
+                outcome.error.map(|e| e.to_string()),
+                outcome.src_id.map(str::to_owned),
+            ));
+        }));
+
+        let ticket = signer.sign(&test_payload("987654321"));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        assert!(verifier.verify(&ticket, "111111111").is_err());
+        assert!(verifier.verify("not-a-ticket", "987654321").is_err());
+        {
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 3);
+            assert_eq!(seen[0], (None, Some("123456789".to_owned())));
+            assert!(seen[1].0.is_some());
+            assert_eq!(seen[1].1.as_deref(), Some("123456789"));
+            assert_eq!(seen[2], (Some(TicketError::BadPrefix.to_string()), None));
+        }
+
+        // 自定义前缀的失败票据同样能解出声称的 src_id
+        verifier.with_prefix("ACME:v1:").unwrap();
+        let acme = ticket.replacen(TICKET_PREFIX, "ACME:v1:", 1);
+        assert!(verifier.verify(&acme, "111111111").is_err());
+        assert_eq!(seen.lock().unwrap()[3].1.as_deref(), Some("123456789"));
+
+        // 回调 panic 不影响验证结果
+        verifier.with_on_result(Box::new(|_| panic!("hook")));
+        assert!(verifier.verify(&acme, "987654321").is_ok());
+    }
+
+    #[test]
+    fn test_verify_detailed() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 1_060;
+        let ticket = signer.sign(&payload);
+
+        verifier.with_clock(Box::new(FixedClock(1_020)));
+        let verified = verifier.verify_detailed(&ticket, "987654321").unwrap();
+        assert_eq!(verified.payload.src_id, "123456789");
+        assert_eq!(verified.remaining, Duration::from_secs(40));
+
+        // 处于时钟偏差容忍范围内: 仍然通过, 剩余时间为 0
+        verifier.with_clock(Box::new(FixedClock(1_070)));
+        let verified = verifier.verify_detailed(&ticket, "987654321").unwrap();
+        assert_eq!(verified.remaining, Duration::ZERO);
+        assert!(verified.timings.signature > Duration::ZERO);
+        assert!(verified.timings.parse > Duration::ZERO);
+
+        // 命中验签缓存时不再解析和验签
+        verifier.with_verify_cache(16);
+        verifier.verify_detailed(&ticket, "987654321").unwrap();
+        let verified = verifier.verify_detailed(&ticket, "987654321").unwrap();
+        assert_eq!(verified.timings, VerifyTimings::default());
+    }
+
+    #[test]
+    fn test_soft_expiry() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 1_300;
+        let ticket = signer.sign(&payload);
+
+        // 未设置阈值时不提示
+        verifier.with_clock(Box::new(FixedClock(1_299)));
+        assert!(!verifier.verify_detailed(&ticket, "987654321").unwrap().near_expiry);
+
+        verifier.with_soft_expiry(60).unwrap();
+        // 剩余 61 秒
+        verifier.with_clock(Box::new(FixedClock(1_239)));
+        assert!(!verifier.verify_detailed(&ticket, "987654321").unwrap().near_expiry);
+        // 剩余 60 秒, 恰好不低于阈值
+        verifier.with_clock(Box::new(FixedClock(1_240)));
+        assert!(!verifier.verify_detailed(&ticket, "987654321").unwrap().near_expiry);
+        // 剩余 59 秒
+        verifier.with_clock(Box::new(FixedClock(1_241)));
+        let verified = verifier.verify_detailed(&ticket, "987654321").unwrap();
+        assert!(verified.near_expiry);
+        assert_eq!(verified.remaining, Duration::from_secs(59));
+
+        assert!(verifier.with_soft_expiry(-1).is_err());
+    }
+
+    #[test]
+    fn test_expiry_grace() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_clock_skew(10).unwrap();
+        verifier.with_expiry_grace(5).unwrap();
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 1_300;
+        let ticket = signer.sign(&payload);
+        let detailed = |verifier: &mut TicketVerifier, now: i64| {
+            verifier.with_clock(Box::new(FixedClock(now)));
+            verifier.verify_detailed(&ticket, "987654321")
+        };
+
+        // 时钟偏差范围内不算过期
+        assert!(!detailed(&mut verifier, 1_310).unwrap().expired_within_grace);
+        let verified = detailed(&mut verifier, 1_311).unwrap();
+        assert!(verified.expired_within_grace);
+        assert_eq!(verified.remaining, Duration::ZERO);
+        assert!(detailed(&mut verifier, 1_315).unwrap().expired_within_grace);
+        assert!(matches!(
+            detailed(&mut verifier, 1_316),
+            Err(TicketError::Expired)
+        ));
+
+        // 其它验证方法不使用宽限期
+        assert!(matches!(
+            verifier.verify_at(&ticket, "987654321", 1_311),
+            Err(TicketError::Expired)
+        ));
+        assert!(verifier.with_expiry_grace(-1).is_err());
+    }
+
+    #[test]
+    fn test_deny_unknown_fields() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut value = serde_json::to_value(test_payload("987654321")).unwrap();
+        value["exipry"] = serde_json::json!(0);
+        let payload_bytes = serde_json::to_vec(&value).unwrap();
+        let key = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+        let sign = |bytes: &[u8]| {
+            use ed25519_dalek::Signer;
+            format!(
+                "{}{}.{}",
+                TICKET_PREFIX,
+                b64_encode(bytes),
+                b64_encode(&key.sign(bytes).to_bytes())
+            )
+        };
+        let typo = sign(&payload_bytes);
+        let normal = signer.sign(&test_payload("987654321"));
+        let v2 = signer.sign_v2(&test_payload("987654321"));
+
+        // 默认忽略未知字段
+        assert!(verifier.verify(&typo, "987654321").is_ok());
+
+        verifier.with_deny_unknown_fields(true);
+        assert!(matches!(
+            verifier.verify(&typo, "987654321"),
+            Err(TicketError::UnknownField(field)) if field == "exipry"
+        ));
+        assert!(verifier.verify(&normal, "987654321").is_ok());
+        assert!(verifier.verify(&v2, "987654321").is_ok());
+
+        // 嵌套对象中的未知字段同样拒绝
+        value.as_object_mut().unwrap().remove("exipry");
+        value["dlg"] = serde_json::json!({"key": "", "exp": 0, "sig": "", "extra": 1});
+        assert!(matches!(
+            verifier.verify(&sign(&serde_json::to_vec(&value).unwrap()), "987654321"),
+            Err(TicketError::UnknownField(field)) if field.ends_with(".extra")
+        ));
+    }
+
+    #[test]
+    fn test_hashed_dst_id() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        assert_eq!(
+            hash_device_id("salt", "987654321"),
+            hex::encode(Sha256::digest(b"salt987654321"))
+        );
+
+        let mut payload = test_payload("987654321");
+        payload.dst_id = DstId::default();
+        payload.dst_salt = Some("a1b2c3d4".to_owned());
+        payload.dst_id_hash = Some(hash_device_id("a1b2c3d4", "987654321"));
+        let ticket = signer.sign(&payload);
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&ticket, "111111111"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+        // 哈希大小写不敏感
+        payload.dst_id_hash = payload.dst_id_hash.map(|h| h.to_ascii_uppercase());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+
+        // 携带哈希时忽略 dst_id
+        payload.dst_id = "111111111".into();
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "111111111"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+        // 缺少盐
+        payload.dst_salt = None;
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::MalformedDeviceId)
+        ));
+    }
+
+    #[test]
+    fn test_with_public_key_hex() {
+        let signer = test_signer(1);
+        let verifier = TicketVerifier::with_public_key_hex(&signer.verifying_key_hex()).unwrap();
+        assert!(verifier.is_ready());
+        assert!(verifier
+            .verify(&signer.sign(&test_payload("987654321")), "987654321")
+            .is_ok());
+
+        // 不在曲线上的点在构造时即被拒绝
+        let invalid_point = format!("02{}", "00".repeat(31));
+        assert!(matches!(
+            TicketVerifier::with_public_key_hex(&invalid_point),
+            Err(TicketError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            TicketVerifier::with_public_key_hex("abcd"),
+            Err(TicketError::BadKeyLength(2))
+        ));
+    }
+
+    #[test]
+    fn test_strict_claims() {
+        let mut signer = test_signer(1);
+        // 保持空 nonce, 不由签发器自动生成
+        signer.with_nonce_fn(String::new);
+        let mut verifier = TicketVerifier::new();
+        verifier
+            .add_public_key_hex_with_id("k1", &signer.verifying_key_hex())
+            .unwrap();
+        let mut payload = test_payload("987654321");
+        payload.kid = Some("k1".to_owned());
+        let complete = signer.sign(&payload);
+        let mut no_kid = payload.clone();
+        no_kid.kid = None;
+        let no_kid = signer.sign(&no_kid);
+        payload.nonce.clear();
+        let no_nonce = signer.sign(&payload);
+
+        // 默认不要求 kid
+        assert!(verifier.verify(&no_kid, "987654321").is_ok());
+
+        verifier.with_strict_claims(true);
+        assert!(verifier.verify(&complete, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&no_kid, "987654321"),
+            Err(TicketError::MissingRequiredClaim("kid"))
+        ));
+        assert!(matches!(
+            verifier.verify(&no_nonce, "987654321"),
+            Err(TicketError::MissingRequiredClaim("nonce"))
+        ));
+    }
+
+    #[test]
+    fn test_binary_ticket() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let payload = test_payload("987654321");
+
+        let text = signer.sign_v2(&payload);
+        let (payload_bytes, signature) = split_ticket(&text).unwrap();
+        let frame = encode_binary(TicketVersion::V2, &payload_bytes, &signature).unwrap();
+        assert_eq!(frame.len(), 3 + payload_bytes.len() + 64);
+        assert!(frame.len() < text.len());
+        assert_eq!(
+            verifier.verify_binary(&frame, "987654321").unwrap().src_id,
+            "123456789"
+        );
+        assert_eq!(signer.sign_binary(&payload).unwrap(), frame);
+
+        let text = signer.sign(&payload);
+        let (payload_bytes, signature) = split_ticket(&text).unwrap();
+        let frame = encode_binary(TicketVersion::V1, &payload_bytes, &signature).unwrap();
+        assert!(verifier.verify_binary(&frame, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify_binary(&frame, "111111111"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+
+        // 异常帧: 过短、版本号未知、长度与声明不符
+        let mut unknown_version = frame.clone();
+        unknown_version[0] = 9;
+        let mut bad_signature = frame.clone();
+        *bad_signature.last_mut().unwrap() ^= 1;
+        for malformed in [
+            &frame[..2],
+            &unknown_version[..],
+            &frame[..frame.len() - 1],
+            &[frame.as_slice(), &[0]].concat(),
+        ] {
+            assert!(matches!(
+                verifier.verify_binary(malformed, "987654321"),
+                Err(TicketError::MalformedStructure)
+            ));
+        }
+        assert!(matches!(
+            verifier.verify_binary(&bad_signature, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+
+        // 载荷超出长度字段的表示范围
+        assert!(matches!(
+            encode_binary(TicketVersion::V2, &vec![0; 65_536], &signature),
+            Err(TicketError::PayloadTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_matched_kid() {
+        let (old, new) = (test_signer(1), test_signer(2));
+        let mut verifier = TicketVerifier::new();
+        verifier
+            .add_public_key_hex_with_id("old", &old.verifying_key_hex())
+            .unwrap();
+        verifier
+            .add_public_key_hex_with_id("new", &new.verifying_key_hex())
+            .unwrap();
+        verifier.with_verify_cache(8);
+
+        // 票据未携带 kid 时依次尝试, 报告实际验签的公钥; 命中缓存时结果相同
+        for (signer, kid) in [(&old, "old"), (&new, "new")] {
+            let ticket = signer.sign(&test_payload("987654321"));
+            for _ in 0..2 {
+                let verified = verifier.verify_detailed(&ticket, "987654321").unwrap();
+                assert_eq!(verified.matched_kid.as_deref(), Some(kid));
+            }
+        }
+        let compressed = new.sign_v2_compressed(&test_payload("987654321"));
+        let verified = verifier.verify_detailed(&compressed, "987654321").unwrap();
+        assert_eq!(verified.matched_kid.as_deref(), Some("new"));
+
+        // 未设置 kid 的公钥报告指纹
+        let verifier = test_verifier(&old);
+        let verified = verifier
+            .verify_detailed(&old.sign(&test_payload("987654321")), "987654321")
+            .unwrap();
+        assert_eq!(verified.matched_kid, Some(verifier.key_fingerprints()[0].clone()));
+    }
+
+    #[test]
+    fn test_renewal_metadata() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+
+        // 未携带时缺省为不可续期, 也不出现在载荷中
+        let payload = test_payload("987654321");
+        assert!(!canonical_json(&payload).windows(5).any(|w| w == b"renew"));
+        let verified = verifier.verify(&signer.sign(&payload), "987654321").unwrap();
+        assert_eq!(verified.renewable, None);
+        assert!(!verified.is_renewable());
+        assert_eq!(verified.renew_url, None);
+
+        let mut payload = test_payload("987654321");
+        payload.renewable = Some(true);
+        payload.renew_url = Some("https://api.example.com/ticket/renew".to_owned());
+        for ticket in [signer.sign(&payload), signer.sign_v2(&payload)] {
+            let verified = verifier.verify(&ticket, "987654321").unwrap();
+            assert!(verified.is_renewable());
+            assert_eq!(verified.renew_url, payload.renew_url);
+        }
+    }
+
+    #[test]
+    fn test_min_iat() {
+        let signer = test_signer(1);
+        let verifier = Arc::new(test_verifier(&signer));
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 1_300;
+        let ticket = signer.sign(&payload);
+        assert!(verifier.verify_at(&ticket, "987654321", 1_100).is_ok());
+
+        // 通过共享引用更新, 其它持有者立即生效
+        let shared = verifier.clone();
+        shared.set_min_iat(1_001);
+        assert!(matches!(
+            verifier.verify_at(&ticket, "987654321", 1_100),
+            Err(TicketError::IssuedBeforeCutoff)
+        ));
+        shared.set_min_iat(1_000);
+        assert!(verifier.verify_at(&ticket, "987654321", 1_100).is_ok());
+        shared.set_min_iat(i64::MIN);
+        assert!(verifier.verify_at(&ticket, "987654321", 1_100).is_ok());
+
+        // 克隆的句柄共享截止时间, 修改其它配置后带着当时的值分离
+        let mut forked = (*verifier).clone();
+        verifier.set_min_iat(1_001);
+        assert!(forked.verify_at(&ticket, "987654321", 1_100).is_err());
+        forked.with_expiry_grace(0).unwrap();
+        verifier.set_min_iat(i64::MIN);
+        assert!(matches!(
+            forked.verify_at(&ticket, "987654321", 1_100),
+            Err(TicketError::IssuedBeforeCutoff)
+        ));
+        assert!(verifier.verify_at(&ticket, "987654321", 1_100).is_ok());
+        assert_eq!(TicketError::IssuedBeforeCutoff.code(), "ISSUED_BEFORE_CUTOFF");
+    }
+
+    #[test]
+    fn test_verify_at_boundaries() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.nbf = Some(1_000);
+        payload.exp = 1_060;
+        let ticket = signer.sign(&payload);
+
+        // 默认 30 秒偏差: 有效区间为 [970, 1090]
+        assert!(matches!(
+            verifier.verify_at(&ticket, "987654321", 969),
+            Err(TicketError::NotYetValid)
+        ));
+        assert!(verifier.verify_at(&ticket, "987654321", 970).is_ok());
+        assert!(verifier.verify_at(&ticket, "987654321", 1_090).is_ok());
+        assert!(matches!(
+            verifier.verify_at(&ticket, "987654321", 1_091),
+            Err(TicketError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_custom_signature_scheme() {
+        // 仅用于测试的 "算法": 签名为消息逆序
+        struct ReverseScheme;
+
+        impl SignatureScheme for ReverseScheme {
+            fn algorithm(&self) -> &str {
+                "X-REVERSE"
+            }
+
+            fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<(), ()> {
+                if msg.iter().rev().eq(sig.iter()) {
+                    Ok(())
+                } else {
+                    Err(())
+                }
+            }
+        }
+
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.add_public_key_scheme(None, Box::new(ReverseScheme));
+
+        let mut payload = test_payload("987654321");
+        payload.alg = Some("X-REVERSE".to_owned());
+        let payload_bytes = serde_json::to_vec(&payload).unwrap();
+        let signature: Vec<u8> = payload_bytes.iter().rev().copied().collect();
+        let ticket = format!(
+            "{}{}.{}",
+            TICKET_PREFIX,
+            b64_encode(&payload_bytes),
+            b64_encode(&signature)
+        );
+
+        // 混合部署: 两种算法的票据都能通过
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        assert!(verifier
+            .verify(&signer.sign(&test_payload("987654321")), "987654321")
+            .is_ok());
+
+        // 未知算法或算法不匹配的签名被拒绝
+        payload.alg = Some("X-UNKNOWN".to_owned());
+        let forged = signer.sign(&payload);
+        assert!(matches!(
+            verifier.verify(&forged, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_authenticate_password() {
+        let signer = test_signer(1);
+        let public_key = signer.verifying_key_hex();
+        let ticket = signer.sign(&test_payload("987654321"));
+
+        assert!(matches!(
+            authenticate_password(ticket.as_bytes(), "987654321", &public_key),
+            AuthResult::Ticket(p) if p.src_id == "123456789"
+        ));
+        assert!(matches!(
+            authenticate_password(b"hunter2", "987654321", &public_key),
+            AuthResult::NotTicket
+        ));
+        assert!(matches!(
+            authenticate_password(ticket.as_bytes(), "111111111", &public_key),
+            AuthResult::TicketInvalid(TicketError::DeviceMismatch { .. })
+        ));
+        assert!(matches!(
+            authenticate_password(ticket.as_bytes(), "987654321", ""),
+            AuthResult::TicketInvalid(TicketError::MissingPublicKey)
+        ));
+        // 本机设备 ID 为空时不跳过目标设备校验
+        assert!(matches!(
+            authenticate_password(ticket.as_bytes(), "", &public_key),
+            AuthResult::TicketInvalid(TicketError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            authenticate_password(b"hunter2", "", &public_key),
+            AuthResult::NotTicket
+        ));
+        assert!(matches!(
+            classify_and_verify(b"hunter2", "987654321", &public_key),
+            TicketOutcome::NotTicket
+        ));
+        assert!(matches!(
+            classify_and_verify(ticket.as_bytes(), "987654321", &public_key),
+            TicketOutcome::Valid(p) if p.src_id == "123456789"
+        ));
+        assert!(matches!(
+            classify_and_verify(ticket.as_bytes(), "111111111", &public_key),
+            TicketOutcome::Invalid(TicketError::DeviceMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let check = |password: &[u8]| password == b"hunter2";
+
+        assert!(matches!(
+            authenticate(ticket.as_bytes(), "987654321", &verifier, check),
+            AuthDecision::Ticket(p) if p.src_id == "123456789"
+        ));
+        assert!(matches!(
+            authenticate(b"hunter2", "987654321", &verifier, check),
+            AuthDecision::Password
+        ));
+        assert!(matches!(
+            authenticate(b"wrong", "987654321", &verifier, check),
+            AuthDecision::Denied(None)
+        ));
+        assert!(matches!(
+            authenticate(ticket.as_bytes(), "111111111", &verifier, check),
+            AuthDecision::Denied(Some(TicketError::DeviceMismatch { .. }))
+        ));
+        // 畸形票据直接拒绝, 即使共享密码恰好相同也不放行
+        for malformed in [&b"TICKET:v3:abc.def"[..], b"TICKET:v1:abc", b"TICKET:\xff"] {
+            assert!(matches!(
+                authenticate(malformed, "987654321", &verifier, |_| true),
+                AuthDecision::Denied(Some(_))
+            ));
+        }
+        // authenticate_password 对未知版本和畸形票据的判断与之相同
+        for malformed in [&b"TICKET:v3:abc.def"[..], b"TICKET:garbage"] {
+            assert!(matches!(
+                authenticate_password(malformed, "987654321", &signer.verifying_key_hex()),
+                AuthResult::TicketInvalid(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_verify_bytes() {
+        use std::sync::atomic::AtomicUsize;
+
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let observed = Arc::new(AtomicUsize::new(0));
+        let counter = observed.clone();
+        verifier.with_on_result(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+        let ticket = signer.sign(&test_payload("987654321"));
+
+        let from_bytes = verifier.verify_bytes(ticket.as_bytes(), "987654321").unwrap();
+        let from_str = verifier.verify(&ticket, "987654321").unwrap();
+        assert_eq!(canonical_json(&from_bytes), canonical_json(&from_str));
+        assert!(matches!(
+            verifier.verify_bytes(ticket.as_bytes(), "111111111"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+
+        let mut non_ascii = ticket.clone().into_bytes();
+        non_ascii[20] = 0xff;
+        assert!(matches!(
+            verifier.verify_bytes(&non_ascii, "987654321"),
+            Err(TicketError::Base64(_))
+        ));
+        assert!(matches!(
+            verifier.verify_bytes("密码".as_bytes(), "987654321"),
+            Err(TicketError::BadPrefix)
+        ));
+        assert!(matches!(
+            verifier.verify_bytes(b"", "987654321"),
+            Err(TicketError::EmptyTicket)
+        ));
+        // 每条路径都经过结果回调
+        assert_eq!(observed.load(Ordering::Relaxed), 6);
+    }
+
+    #[test]
+    fn test_empty_ticket() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        for input in ["", "   ", "\t\r\n", "TICKET:v1:", "TICKET:v2: "] {
+            assert!(
+                matches!(verifier.verify(input, "987654321"), Err(TicketError::EmptyTicket)),
+                "{:?}",
+                input
+            );
+        }
+        assert_eq!(TicketError::EmptyTicket.code(), "EMPTY_TICKET");
+
+        // 分类时空输入明确视为不是票据
+        let public_key = signer.verifying_key_hex();
+        for input in [&b""[..], b"  "] {
+            assert!(matches!(
+                classify_and_verify(input, "987654321", &public_key),
+                TicketOutcome::NotTicket
+            ));
+        }
+        assert!(matches!(
+            classify_and_verify(b"TICKET:v1:", "987654321", &public_key),
+            TicketOutcome::Invalid(TicketError::EmptyTicket)
+        ));
+    }
+
+    #[test]
+    fn test_custom_prefix() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let tenant_ticket = ticket.replacen("TICKET:", "ACME:", 1);
+        let tenant_ticket_v2 = signer
+            .sign_v2(&test_payload("987654321"))
+            .replacen("TICKET:", "ACME:", 1);
+
+        assert!(verifier.is_ticket(ticket.as_bytes()));
+        assert!(!verifier.is_ticket(tenant_ticket.as_bytes()));
+
+        verifier.with_prefix("ACME:v1:").unwrap();
+        assert!(verifier.is_ticket(tenant_ticket.as_bytes()));
+        assert!(!verifier.is_ticket(ticket.as_bytes()));
+        assert!(verifier.verify(&tenant_ticket, "987654321").is_ok());
+        assert!(verifier.verify(&tenant_ticket_v2, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::BadPrefix)
+        ));
+
+        // 模块级 is_ticket 始终使用默认前缀
+        assert!(is_ticket(ticket.as_bytes()));
+        assert!(!is_ticket(tenant_ticket.as_bytes()));
+
+        for bad in ["ACME:", "v1:", "ACME:v3:"] {
+            assert!(matches!(
+                verifier.with_prefix(bad),
+                Err(TicketError::ConfigurationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_multiple_prefixes() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let old = signer.sign(&test_payload("987654321"));
+        let new = signer
+            .sign_v2(&test_payload("987654321"))
+            .replacen("TICKET:", "ACME:", 1);
+        let other = old.replacen("TICKET:", "OTHER:", 1);
+
+        verifier.add_prefix("ACME:v2:").unwrap();
+        for ticket in [&old, &new] {
+            assert!(verifier.is_ticket(ticket.as_bytes()));
+            assert!(verifier.verify(ticket, "987654321").is_ok());
+        }
+        assert!(!verifier.is_ticket(other.as_bytes()));
+        assert!(matches!(
+            verifier.verify(&other, "987654321"),
+            Err(TicketError::BadPrefix)
+        ));
+        assert!(verifier.add_prefix("ACME").is_err());
+
+        // with_prefix 替换全部前缀
+        verifier.with_prefix("ACME:v1:").unwrap();
+        assert!(!verifier.is_ticket(old.as_bytes()));
+        assert!(verifier.is_ticket(new.as_bytes()));
+    }
+
+    #[test]
+    fn test_weak_nonce() {
+        let mut signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        // 签发器默认为空 nonce 自动生成随机值, 这里保持为空
+        signer.with_nonce_fn(String::new);
+        let mut payload = test_payload("987654321");
+        for nonce in ["", "0123456789abcde"] {
+            payload.nonce = nonce.to_owned();
+            assert!(matches!(
+                verifier.verify(&signer.sign(&payload), "987654321"),
+                Err(TicketError::WeakNonce)
+            ));
+        }
+
+        payload.nonce = "0123456789abcdef".to_owned();
+        let ticket = signer.sign(&payload);
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        verifier.with_min_nonce_len(32).unwrap();
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::WeakNonce)
+        ));
+        assert!(matches!(
+            verifier.with_min_nonce_len(0),
+            Err(TicketError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_payload_debug_redacts_nonce() {
+        let payload = test_payload("987654321");
+        let debug = format!("{:?}", payload);
+        assert!(debug.contains("\"0123…\""), "{}", debug);
+        assert!(!debug.contains("0123456789abcdef"));
+        assert!(format!("{:?}", payload.full_debug()).contains("0123456789abcdef"));
+
+        let display = payload.to_string();
+        assert!(display.starts_with("ticket src=123456789 dst=987654321 exp="));
+        assert!(display.ends_with("s)"), "{}", display);
+        assert_eq!(redact("abc"), "abc");
+    }
+
+    mod prop {
+        use super::*;
+        use ed25519_dalek::{Signer, SigningKey};
+        use proptest::prelude::*;
+
+        /// 用测试私钥对任意字节签名, 让畸形载荷越过验签进入解析阶段
+        fn sign_raw(prefix: &str, payload: &[u8]) -> String {
+            let signature = SigningKey::from_bytes(&[1u8; 32]).sign(payload);
+            format!(
+                "{}{}.{}",
+                prefix,
+                b64_encode(payload),
+                b64_encode(&signature.to_bytes())
+            )
+        }
+
+        proptest! {
+            #[test]
+            fn arbitrary_input_never_panics(input in any::<String>()) {
+                let verifier = test_verifier(&test_signer(1));
+                prop_assert!(verifier.verify(&input, "987654321").is_err());
+                let _ = decode_payload_unverified(&input);
+                let _ = authenticate_password(input.as_bytes(), "987654321", "");
+            }
+
+            #[test]
+            fn prefixed_body_never_panics(
+                body in "[A-Za-z0-9_\\-.=+/]{0,256}",
+                v2 in any::<bool>(),
+            ) {
+                let prefix = if v2 { TICKET_PREFIX_V2 } else { TICKET_PREFIX };
+                let ticket = format!("{}{}", prefix, body);
+                let verifier = test_verifier(&test_signer(1));
+                prop_assert!(verifier.verify(&ticket, "987654321").is_err());
+            }
+
+            #[test]
+            fn base64_segments_never_panic(
+                payload in proptest::collection::vec(any::<u8>(), 0..512),
+                signature in proptest::collection::vec(any::<u8>(), 0..96),
+            ) {
+                let ticket = format!(
+                    "{}{}.{}",
+                    TICKET_PREFIX,
+                    b64_encode(&payload),
+                    b64_encode(&signature)
+                );
+                let verifier = test_verifier(&test_signer(1));
+                prop_assert!(verifier.verify(&ticket, "987654321").is_err());
+            }
+
+            #[test]
+            fn signed_garbage_payload_never_panics(
+                payload in proptest::collection::vec(any::<u8>(), 0..512),
+                v2 in any::<bool>(),
+            ) {
+                let prefix = if v2 { TICKET_PREFIX_V2 } else { TICKET_PREFIX };
+                let verifier = test_verifier(&test_signer(1));
+                let _ = verifier.verify(&sign_raw(prefix, &payload), "987654321");
+            }
+
+            #[test]
+            fn signed_json_with_odd_fields_never_panics(
+                src_id in any::<String>(),
+                exp in any::<i64>(),
+                iat in any::<i64>(),
+                nbf in any::<Option<i64>>(),
+            ) {
+                let json = serde_json::json!({
+                    "src_id": src_id,
+                    "dst_id": "987654321",
+                    "exp": exp,
+                    "nonce": "0123456789abcdef",
+                    "iat": iat,
+                    "nbf": nbf,
+                });
+                let mut verifier = test_verifier(&test_signer(1));
+                verifier.with_max_lifetime(600).unwrap();
+                let ticket = sign_raw(TICKET_PREFIX, json.to_string().as_bytes());
+                let _ = verifier.verify(&ticket, "987654321");
+                let _ = verifier.verify_at(&ticket, "987654321", i64::MAX);
+                let _ = verifier.verify_at(&ticket, "987654321", i64::MIN);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dst_id_matching() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        let check = |payload: &TicketPayload, device_id: &str| {
+            verifier.verify(&signer.sign(payload), device_id).is_ok()
+        };
+
+        // 单个字符串: 序列化格式不变
+        assert!(serde_json::to_string(&payload)
+            .unwrap()
+            .contains(r#""dst_id":"987654321""#));
+        assert!(check(&payload, "987654321"));
+        assert!(!check(&payload, "98765432"));
+
+        payload.dst_id = vec!["111111111".to_owned(), "222222222".to_owned()].into();
+        assert!(check(&payload, "222222222"));
+        assert!(!check(&payload, "333333333"));
+        assert_eq!(payload.dst_id.to_string(), "111111111,222222222");
+
+        payload.dst_id = "site-7-*".into();
+        assert!(check(&payload, "site-7-kiosk-01"));
+        assert!(!check(&payload, "site-8-kiosk-01"));
+        assert!(!check(&payload, "site-7"));
+
+        payload.dst_id = vec!["site-7-*".to_owned(), "987654321".to_owned()].into();
+        assert!(check(&payload, "site-7-1"));
+        assert!(check(&payload, "987654321"));
+
+        // 单独的 "*" 不匹配任何设备
+        payload.dst_id = "*".into();
+        assert!(!check(&payload, "987654321"));
+
+        let decoded: TicketPayload = serde_json::from_str(
+            r#"{"src_id":"1","dst_id":["a","b*"],"exp":0,"nonce":"n","iat":0}"#,
+        )
+        .unwrap();
+        assert!(decoded.dst_id.matches("bc"));
+    }
+
+    #[test]
+    fn test_malformed_device_id() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_device_binding(DeviceBinding::AnyDevice);
+        let verify = |verifier: &TicketVerifier, src_id: &str, dst_id: DstId| {
+            let mut payload = test_payload("987654321");
+            payload.src_id = src_id.to_owned();
+            payload.dst_id = dst_id;
+            verifier.verify(&signer.sign(&payload), "987654321")
+        };
+        let malformed = |result: Result<TicketPayload, TicketError>| {
+            matches!(result, Err(TicketError::MalformedDeviceId))
+        };
+
+        assert!(verify(&verifier, "123456789", "site-7.kiosk_01@hbbs".into()).is_ok());
+        assert!(verify(&verifier, "123456789", "site-7-*".into()).is_ok());
+        assert!(malformed(verify(&verifier, "", "987654321".into())));
+        assert!(malformed(verify(&verifier, "123456789", "".into())));
+        assert!(malformed(verify(&verifier, "123456789", "*".into())));
+        assert!(malformed(verify(&verifier, "123456789", Vec::new().into())));
+        assert!(malformed(verify(&verifier, "123456789", vec![String::new()].into())));
+        assert!(malformed(verify(&verifier, &"1".repeat(65), "987654321".into())));
+        assert!(verify(&verifier, &"1".repeat(64), "987654321".into()).is_ok());
+        assert!(malformed(verify(&verifier, "123 456", "987654321".into())));
+        assert!(malformed(verify(&verifier, "123456789", "98765432١".into())));
+
+        verifier.with_device_id_format(9, "").unwrap();
+        assert!(verify(&verifier, "123456789", "987654321".into()).is_ok());
+        assert!(malformed(verify(&verifier, "1234567890", "987654321".into())));
+        assert!(malformed(verify(&verifier, "123456789", "site-7".into())));
+        assert!(verifier.with_device_id_format(0, "").is_err());
+        assert!(verifier.with_device_id_format(64, "*").is_err());
+    }
+
+    #[test]
+    fn test_key_fingerprints() {
+        let key_hex = "097b66390cb0efc58cae7471f0e02508ed1a98ff8b9c6c3e32b2c88608123469";
+        let mut verifier = TicketVerifier::new();
+        verifier.add_public_key_hex(key_hex).unwrap();
+        verifier
+            .add_public_key_hex_with_id("k2", &test_signer(2).verifying_key_hex())
+            .unwrap();
 
-        let mut sig_array = [0u8; 64];
-        sig_array.copy_from_slice(&signature_bytes);
-        let signature = Signature::from_bytes(&sig_array);
+        let fingerprints = verifier.key_fingerprints();
+        assert_eq!(fingerprints.len(), 2);
+        // 指纹固定, 可与服务端记录直接比对
+        assert_eq!(fingerprints[0], "4a5ea418153c41ac");
+        assert!(fingerprints[1].starts_with("k2:"), "{}", fingerprints[1]);
+        assert_eq!(fingerprints[1].len(), "k2:".len() + 16);
+    }
+
+    #[test]
+    fn test_load_jwks() {
+        let signer = test_signer(1);
+        let x = URL_SAFE_NO_PAD.encode(hex::decode(signer.verifying_key_hex()).unwrap());
+        let jwks = format!(
+            r#"{{"keys": [
+                {{"kty": "RSA", "kid": "rsa-1", "n": "0vx7agoebGcQSuu", "e": "AQAB"}},
+                {{"kty": "OKP", "crv": "Ed25519", "kid": "ed-1", "x": "{}"}}
+            ]}}"#,
+            x
+        );
+        let mut verifier = TicketVerifier::new();
+        assert_eq!(verifier.load_jwks(&jwks).unwrap(), 1);
+        let fingerprints = verifier.key_fingerprints();
+        assert_eq!(fingerprints.len(), 1);
+        assert!(fingerprints[0].starts_with("ed-1:"), "{}", fingerprints[0]);
+
+        let mut payload = test_payload("987654321");
+        payload.kid = Some("ed-1".to_owned());
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        assert!(matches!(
+            verifier.load_jwks(r#"{"keys": [{"kty": "OKP", "crv": "Ed25519", "x": "AAAA"}]}"#),
+            Err(TicketError::BadKeyLength(3))
+        ));
+        assert!(verifier.load_jwks("not json").is_err());
+    }
+
+    #[test]
+    fn test_config_summary() {
+        let signer = test_signer(1);
+        let mut verifier = TicketVerifier::new();
+        verifier
+            .add_public_key_hex_with_id("k1", &signer.verifying_key_hex())
+            .unwrap();
+        verifier.with_max_lifetime(600).unwrap();
+        verifier.with_expected_audience("rustdesk");
+
+        let summary = verifier.config_summary();
+        assert_eq!(summary.prefixes, ["TICKET:"]);
+        assert_eq!(summary.keys.len(), 1);
+        assert_eq!(summary.keys[0].kid.as_deref(), Some("k1"));
+        assert_eq!(summary.keys[0].fingerprint.as_ref().unwrap().len(), 16);
+        assert_eq!(summary.max_lifetime, Some(600));
+
+        // 任何输出中都不出现完整公钥
+        let display = summary.to_string();
+        let json = serde_json::to_string(&summary).unwrap();
+        for text in [&display, &json] {
+            assert!(!text.contains(&signer.verifying_key_hex()), "{}", text);
+        }
+        assert!(display.contains("aud=rustdesk"), "{}", display);
+        assert!(display.contains("max_lifetime=600"), "{}", display);
+    }
+
+    #[test]
+    fn test_legacy_unversioned() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let v1 = signer.sign(&test_payload("987654321"));
+        let v2 = signer.sign_v2(&test_payload("987654321"));
+        let legacy = v1.replacen(TICKET_PREFIX, DEFAULT_PREFIX_BASE, 1);
+
+        assert!(!verifier.is_ticket(legacy.as_bytes()));
+        assert!(matches!(
+            verifier.verify(&legacy, "987654321"),
+            Err(TicketError::BadPrefix)
+        ));
+
+        verifier.with_legacy_unversioned(true);
+        assert!(verifier.is_ticket(legacy.as_bytes()));
+        assert_eq!(verifier.verify(&legacy, "987654321").unwrap().src_id, "123456789");
+        // 带版本标记的票据照常处理
+        assert!(verifier.verify(&v1, "987654321").is_ok());
+        assert!(verifier.verify(&v2, "987654321").is_ok());
+        assert!(verifier.config_summary().legacy_unversioned);
+    }
 
-        // 验证签名
-        public_key.verify(&payload_bytes, &signature)
-            .map_err(|_| "签名验证失败".to_string())?;
+    #[test]
+    fn test_once_ticket() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.once = true;
+        let ticket = signer.sign(&payload);
+
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::ConfigurationError(_))
+        ));
 
-        // 解析载荷
-        let payload: TicketPayload = serde_json::from_slice(&payload_bytes)
-            .map_err(|e| format!("解析载荷失败: {}", e))?;
+        verifier.with_replay_cache(Arc::new(ReplayCache::new()));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::AlreadyUsed)
+        ));
 
-        // 检查过期时间 (允许 30 秒时钟偏差)
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
+        // 默认值不写入载荷
+        assert!(!serde_json::to_string(&test_payload("987654321"))
             .unwrap()
-            .as_secs() as i64;
-        
-        if payload.exp < now - 30 {
-            return Err("票据已过期".to_string());
+            .contains(r#""once""#));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let payload = TicketPayload {
+            src_id: "123456789".to_owned(),
+            dst_id: "987654321".into(),
+            exp: 1_000_060,
+            nonce: "0123456789abcdef".to_owned(),
+            iat: 1_000_000,
+            ..Default::default()
+        };
+        let canonical = concat!(
+            r#"{"dst_id":"987654321","iat":1000000,"#,
+            r#""nonce":"0123456789abcdef","src_id":"123456789"}"#,
+        );
+        assert_eq!(
+            payload.fingerprint(),
+            hex::encode(&Sha256::digest(canonical.as_bytes())[..8])
+        );
+        assert_eq!(payload.fingerprint().len(), 16);
+
+        // 只覆盖身份相关字段
+        let mut other = payload.clone();
+        other.exp += 60;
+        assert_eq!(other.fingerprint(), payload.fingerprint());
+        other.nonce = "fedcba9876543210".to_owned();
+        assert_ne!(other.fingerprint(), payload.fingerprint());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct CountingClock(Arc<AtomicUsize>);
+
+        impl Clock for CountingClock {
+            fn now_unix(&self) -> i64 {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                unix_time(SystemTime::now())
+            }
         }
 
-        // 检查目标设备 ID
-        if !my_device_id.is_empty() && payload.dst_id != my_device_id {
-            return Err(format!("目标设备 ID 不匹配: 期望 {}, 实际 {}", my_device_id, payload.dst_id));
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        let reads = Arc::new(AtomicUsize::new(0));
+        verifier.with_clock(Box::new(CountingClock(reads.clone())));
+
+        let valid = signer.sign(&test_payload("987654321"));
+        let other = signer.sign(&test_payload("111111111"));
+        let results = verifier.verify_batch(&[
+            (&valid, "987654321"),
+            ("password", "987654321"),
+            (&other, "111111111"),
+            (&other, "987654321"),
+        ]);
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(TicketError::BadPrefix)));
+        assert_eq!(results[2].as_ref().unwrap().dst_id, "111111111");
+        assert!(matches!(results[3], Err(TicketError::DeviceMismatch { .. })));
+    }
+
+    #[test]
+    fn test_channel_binding() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let handshake = b"server-ephemeral-public-key";
+        let mut payload = test_payload("987654321");
+        payload.cb = Some(channel_binding(handshake));
+        let ticket = signer.sign(&payload);
+
+        assert!(verifier
+            .verify_with_channel_binding(&ticket, "987654321", handshake)
+            .is_ok());
+        assert!(matches!(
+            verifier.verify_with_channel_binding(&ticket, "987654321", b"other-connection"),
+            Err(TicketError::ChannelBindingMismatch)
+        ));
+        // 未提供绑定值的入口拒绝已绑定的票据
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::ChannelBindingMismatch)
+        ));
+
+        // 载荷不含 cb 时不检查
+        let unbound = signer.sign(&test_payload("987654321"));
+        assert!(verifier
+            .verify_with_channel_binding(&unbound, "987654321", handshake)
+            .is_ok());
+        assert!(verifier.verify(&unbound, "987654321").is_ok());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_verify_async() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let ticket = signer.sign(&test_payload("987654321"));
+        let rt = hbb_common::tokio::runtime::Builder::new_multi_thread().build().unwrap();
+        rt.block_on(async {
+            let tasks: Vec<_> = (0..8)
+                .map(|_| {
+                    let verifier = verifier.clone();
+                    let ticket = ticket.clone();
+                    hbb_common::tokio::spawn(async move {
+                        verifier.verify_async(&ticket, "987654321").await
+                    })
+                })
+                .collect();
+            for task in tasks {
+                assert_eq!(task.await.unwrap().unwrap().src_id, "123456789");
+            }
+            assert!(matches!(
+                verifier.verify_async(&ticket, "111111111").await,
+                Err(TicketError::DeviceMismatch { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let mut payload = test_payload("987654321");
+        payload.scopes = vec!["file".to_owned()];
+        let json = String::from_utf8(canonical_json(&payload)).unwrap();
+        assert!(json.starts_with(r#"{"dst_id":"987654321","exp":"#), "{}", json);
+        assert!(!json.contains(' '));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "{}", json);
+
+        // 签发方输出的非规范 JSON (多余空白、键顺序不同) 只要签名正确即可通过,
+        // 验签针对的是收到的字节
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let loose = format!(
+            "{{ \"nonce\" : \"0123456789abcdef\",\n \"src_id\": \"123456789\", \
+             \"dst_id\": \"987654321\", \"iat\": {}, \"exp\": {} }}",
+            payload.iat, payload.exp
+        );
+        let signature = SigningKey::from_bytes(&[1u8; 32]).sign(loose.as_bytes());
+        let ticket = format!(
+            "{}{}.{}",
+            TICKET_PREFIX,
+            b64_encode(loose.as_bytes()),
+            b64_encode(&signature.to_bytes())
+        );
+        assert_eq!(verifier.verify(&ticket, "987654321").unwrap().src_id, "123456789");
+
+        // 传输途中改动载荷字节 (哪怕只是空白) 都会使签名失效
+        let ticket = signer.sign(&payload);
+        let (payload_b64, signature_b64) =
+            ticket.strip_prefix(TICKET_PREFIX).unwrap().split_once('.').unwrap();
+        let mut bytes = URL_SAFE_NO_PAD.decode(payload_b64).unwrap();
+        bytes.insert(1, b' ');
+        let tampered = format!("{}{}.{}", TICKET_PREFIX, b64_encode(&bytes), signature_b64);
+        assert!(matches!(
+            verifier.verify(&tampered, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_is_ready() {
+        let mut verifier = TicketVerifier::new();
+        assert!(!verifier.is_ready());
+        assert!(matches!(verifier.ready_or_err(), Err(TicketError::MissingPublicKey)));
+        verifier.set_public_key_hex(&test_signer(1).verifying_key_hex()).unwrap();
+        assert!(verifier.is_ready());
+        assert!(verifier.ready_or_err().is_ok());
+    }
+
+    #[test]
+    fn test_set_public_key_from_env() {
+        let signer = test_signer(1);
+        let var = "RS_CLIENT_TEST_TICKET_PUBLIC_KEY";
+        let mut verifier = TicketVerifier::new();
+
+        std::env::remove_var(var);
+        let err = verifier.set_public_key_from_env(var).unwrap_err();
+        assert!(err.to_string().contains(var), "{}", err);
+
+        std::env::set_var(var, format!("  0x{}\n", signer.verifying_key_hex().to_uppercase()));
+        verifier.set_public_key_from_env(var).unwrap();
+        let ticket = signer.sign(&test_payload("987654321"));
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+
+        std::env::set_var(var, "0xabcd");
+        assert!(matches!(
+            verifier.set_public_key_from_env(var),
+            Err(TicketError::ConfigurationError(_))
+        ));
+        std::env::remove_var(var);
+    }
+
+    #[test]
+    fn test_verify_ignore_expiry() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut payload = test_payload("987654321");
+        payload.iat -= 7200;
+        payload.exp = payload.iat + 60;
+        let ticket = signer.sign(&payload);
+        assert!(matches!(
+            verifier.verify(&ticket, "987654321"),
+            Err(TicketError::Expired)
+        ));
+        assert_eq!(
+            verifier.verify_ignore_expiry(&ticket, "987654321").unwrap().exp,
+            payload.exp
+        );
+
+        // 签名、目标设备和结构仍然检查
+        assert!(matches!(
+            verifier.verify_ignore_expiry(&ticket, "111111111"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+        let other = test_signer(2).sign(&payload);
+        assert!(matches!(
+            verifier.verify_ignore_expiry(&other, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+        assert!(verifier.verify_ignore_expiry("TICKET:v1:abc", "987654321").is_err());
+
+        // 签发后才停用的公钥签出的历史票据照常通过, 停用后签出的仍被拒绝
+        let mut rotated = TicketVerifier::new();
+        rotated
+            .add_public_key_hex_until(None, &signer.verifying_key_hex(), payload.exp)
+            .unwrap();
+        assert!(matches!(
+            rotated.verify(&ticket, "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+        assert!(rotated.verify_ignore_expiry(&ticket, "987654321").is_ok());
+        let compressed = signer.sign_v2_compressed(&payload);
+        assert!(rotated.verify_ignore_expiry(&compressed, "987654321").is_ok());
+        let mut late = payload.clone();
+        late.iat = payload.exp + 1;
+        late.exp = late.iat + 60;
+        for ticket in [signer.sign(&late), signer.sign_v2_compressed(&late)] {
+            assert!(matches!(
+                rotated.verify_ignore_expiry(&ticket, "987654321"),
+                Err(TicketError::SignatureInvalid)
+            ));
         }
 
-        log::info!("票据验证成功: src_id={}, dst_id={}", payload.src_id, payload.dst_id);
-        Ok(payload)
+        // iat 截止时间是实时策略, 不影响审计
+        verifier.set_min_iat(payload.iat + 1);
+        assert!(verifier.verify_ignore_expiry(&ticket, "987654321").is_ok());
     }
-}
 
-/// 检查密码是否为票据格式
-pub fn is_ticket(password: &[u8]) -> bool {
-    if let Ok(s) = std::str::from_utf8(password) {
-        s.starts_with(TICKET_PREFIX)
-    } else {
-        false
+    #[test]
+    fn test_log_level() {
+        assert_eq!(TicketError::BadPrefix.log_level(), log::Level::Debug);
+        assert_eq!(TicketError::MalformedStructure.log_level(), log::Level::Debug);
+        assert_eq!(TicketError::SignatureInvalid.log_level(), log::Level::Warn);
+        assert_eq!(TicketError::Expired.log_level(), log::Level::Warn);
     }
-}
 
-/// 尝试验证票据
-/// 
-/// # 参数
-/// - `password`: 密码字节数组 (可能是票据)
-/// - `my_device_id`: 本机设备 ID
-/// - `public_key_hex`: API Server 的公钥 (十六进制)
-/// 
-/// # 返回
-/// - `Some(TicketPayload)`: 验证成功
-/// - `None`: 不是票据或验证失败
-pub fn try_verify_ticket(password: &[u8], my_device_id: &str, public_key_hex: &str) -> Option<TicketPayload> {
-    let ticket_str = match std::str::from_utf8(password) {
-        Ok(s) => s,
-        Err(_) => return None,
-    };
+    #[test]
+    fn test_duplicate_keys() {
+        let key1 = test_signer(1).verifying_key_hex();
+        let key2 = test_signer(2).verifying_key_hex();
 
-    if !ticket_str.starts_with(TICKET_PREFIX) {
-        return None;
+        // 默认静默去重
+        let mut verifier = TicketVerifier::new();
+        verifier.add_public_key_hex(&key1).unwrap();
+        verifier.add_public_key_hex(&key1).unwrap();
+        verifier.add_public_key_hex_with_id("k1", &key1).unwrap();
+        verifier.add_public_key_hex_with_id("k1", &key1).unwrap();
+        assert_eq!(verifier.config.public_keys.len(), 2);
+
+        // 同一 kid 对应不同公钥一律拒绝
+        assert!(matches!(
+            verifier.add_public_key_hex_with_id("k1", &key2),
+            Err(TicketError::KeyIdConflict(kid)) if kid == "k1"
+        ));
+        verifier.add_public_key_hex_with_id("k2", &key2).unwrap();
+
+        // 启用后重复添加返回错误, 包括同一公钥换了 ID
+        let mut verifier = TicketVerifier::new();
+        verifier.with_reject_duplicate_keys(true);
+        verifier.add_public_key_hex_with_id("k1", &key1).unwrap();
+        assert!(matches!(
+            verifier.add_public_key_hex_with_id("k1", &key1),
+            Err(TicketError::DuplicateKey)
+        ));
+        assert!(matches!(
+            verifier.add_public_key_hex_with_id("k9", &key1),
+            Err(TicketError::DuplicateKey)
+        ));
+        assert_eq!(verifier.config.public_keys.len(), 1);
     }
 
-    let mut verifier = TicketVerifier::new();
-    if let Err(e) = verifier.set_public_key_hex(public_key_hex) {
-        log::warn!("设置票据公钥失败: {}", e);
-        return None;
+    #[test]
+    fn test_delegation() {
+        let anchor = test_signer(1);
+        let subkey = test_signer(2);
+        let mut verifier = TicketVerifier::new();
+        verifier.add_trust_anchor_hex(&anchor.verifying_key_hex()).unwrap();
+        let mut payload = test_payload("987654321");
+        let delegation = anchor.delegate(&subkey, payload.exp + 3600);
+
+        payload.dlg = Some(delegation.clone());
+        assert!(verifier.verify(&subkey.sign(&payload), "987654321").is_ok());
+        assert!(verifier.verify(&subkey.sign_v2(&payload), "987654321").is_ok());
+        // 票据必须由中间私钥签名
+        assert!(matches!(
+            verifier.verify(&test_signer(3).sign(&payload), "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+
+        // 中间公钥不是信任锚签发的
+        payload.dlg = Some(test_signer(3).delegate(&subkey, payload.exp + 3600));
+        assert!(matches!(
+            verifier.verify(&subkey.sign(&payload), "987654321"),
+            Err(TicketError::DelegationInvalid)
+        ));
+        // 篡改委托有效期使签名失效
+        payload.dlg = Some(Delegation {
+            exp: delegation.exp + 1,
+            ..delegation.clone()
+        });
+        assert!(matches!(
+            verifier.verify(&subkey.sign(&payload), "987654321"),
+            Err(TicketError::DelegationInvalid)
+        ));
+        // 票据有效期超出委托范围
+        payload.dlg = Some(anchor.delegate(&subkey, payload.exp - 1));
+        assert!(matches!(
+            verifier.verify(&subkey.sign(&payload), "987654321"),
+            Err(TicketError::DelegationInvalid)
+        ));
+        // 委托已过期
+        let mut expired = test_payload("987654321");
+        expired.iat -= 7200;
+        expired.exp = expired.iat + 60;
+        expired.dlg = Some(anchor.delegate(&subkey, expired.exp));
+        assert!(matches!(
+            verifier.verify(&subkey.sign(&expired), "987654321"),
+            Err(TicketError::DelegationExpired)
+        ));
+
+        // 信任锚不能直接签发普通票据
+        payload.dlg = None;
+        assert!(verifier.verify(&anchor.sign(&payload), "987654321").is_err());
     }
 
-    match verifier.verify(ticket_str, my_device_id) {
-        Ok(payload) => Some(payload),
-        Err(e) => {
-            log::warn!("票据验证失败: {}", e);
-            None
+    #[test]
+    fn test_src_cidr() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let verify = |cidr: Option<&str>, ip: &str| {
+            let mut payload = test_payload("987654321");
+            payload.src_cidr = cidr.map(str::to_owned);
+            verifier.verify_from_peer(&signer.sign(&payload), "987654321", ip.parse().unwrap())
+        };
+
+        assert!(verify(Some("192.168.1.10/32"), "192.168.1.10").is_ok());
+        assert!(matches!(
+            verify(Some("192.168.1.10/32"), "192.168.1.11"),
+            Err(TicketError::AddressNotAllowed)
+        ));
+        assert!(verify(Some("10.0.0.0/8"), "10.255.255.255").is_ok());
+        assert!(verify(Some("10.0.0.0/8"), "11.0.0.0").is_err());
+        assert!(verify(Some("2001:db8::/32"), "2001:db8:ffff::1").is_ok());
+        assert!(verify(Some("2001:db8::/32"), "2001:db9::1").is_err());
+        assert!(verify(Some("2001:db8::/32"), "192.168.1.10").is_err());
+        assert!(verify(Some("not-a-cidr"), "192.168.1.10").is_err());
+        // 未携带 src_cidr 时不检查
+        assert!(verify(None, "192.168.1.10").is_ok());
+
+        // 未提供对端地址的入口拒绝已绑定网络的票据
+        let mut payload = test_payload("987654321");
+        payload.src_cidr = Some("192.168.1.10/32".to_owned());
+        assert!(matches!(
+            verifier.verify(&signer.sign(&payload), "987654321"),
+            Err(TicketError::AddressNotAllowed)
+        ));
+    }
+
+    #[test]
+    fn test_ticket_time_info() {
+        let signer = test_signer(1);
+        let payload = test_payload("987654321");
+        let info = ticket_time_info(&signer.sign(&payload)).unwrap();
+        assert_eq!((info.iat, info.exp), (payload.iat, payload.exp));
+        assert!(info.remaining <= Duration::from_secs(60));
+        assert!(info.remaining >= Duration::from_secs(55));
+
+        let mut expired = test_payload("987654321");
+        expired.exp = expired.iat - 10;
+        let info = ticket_time_info(&signer.sign_v2(&expired)).unwrap();
+        assert_eq!(info.remaining, Duration::ZERO);
+
+        assert!(ticket_time_info("hunter2").is_none());
+    }
+
+    #[test]
+    fn test_peek_exp() {
+        let signer = test_signer(1);
+        let payload = test_payload("987654321");
+        assert_eq!(peek_exp(&signer.sign(&payload)), Some(payload.exp));
+        assert_eq!(peek_exp(&signer.sign_v2(&payload)), Some(payload.exp));
+        assert_eq!(peek_exp(&signer.sign_v2_compressed(&payload)), Some(payload.exp));
+
+        assert_eq!(peek_exp("hunter2"), None);
+        assert_eq!(peek_exp("TICKET:v1:!!!.sig"), None);
+        let no_exp = format!("TICKET:v1:{}.sig", b64_encode(br#"{"src_id":"1"}"#));
+        assert_eq!(peek_exp(&no_exp), None);
+        // 超出大小上限的载荷段不解码
+        let oversized = format!("TICKET:v1:{}.sig", "A".repeat(DEFAULT_MAX_PAYLOAD_BYTES * 2));
+        assert_eq!(peek_exp(&oversized), None);
+    }
+
+    #[test]
+    fn test_verify_reader() {
+        let signer = test_signer(1);
+        let verifier = test_verifier(&signer);
+        let mut input = Vec::new();
+        input.extend_from_slice(signer.sign(&test_payload("987654321")).as_bytes());
+        input.extend_from_slice(b"\n\nhunter2\r\n");
+        input.extend_from_slice(signer.sign_v2(&test_payload("111111111")).as_bytes());
+        input.extend_from_slice(b"\n\xff\xfe\n  ");
+        input.extend_from_slice(signer.sign(&test_payload("987654321")).as_bytes());
+
+        let results: Vec<_> = verifier
+            .verify_reader(std::io::Cursor::new(input), "987654321")
+            .collect();
+        assert_eq!(results.len(), 6);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(TicketError::BadPrefix)));
+        assert!(matches!(results[2], Err(TicketError::BadPrefix)));
+        assert!(matches!(results[3], Err(TicketError::DeviceMismatch { .. })));
+        assert!(matches!(results[4], Err(TicketError::Io(_))));
+        assert!(results[5].is_ok());
+
+        // 超长的行只读入上限以内的部分, 报告后从下一行继续
+        let mut verifier = test_verifier(&signer);
+        verifier.with_max_payload_bytes(256).unwrap();
+        let ticket = signer.sign(&test_payload("987654321"));
+        let mut input = format!("TICKET:v1:{}\n", "A".repeat(100_000)).into_bytes();
+        input.extend_from_slice(ticket.as_bytes());
+        input.extend_from_slice(b"\n");
+        input.extend_from_slice("B".repeat(100_000).as_bytes());
+        let results: Vec<_> = verifier
+            .verify_reader(std::io::Cursor::new(input), "987654321")
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], Err(TicketError::PayloadTooLarge)));
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(TicketError::PayloadTooLarge)));
+
+        // 读取出错后结束迭代, 不会无限产生错误
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "broken"))
+            }
         }
+
+        let results: Vec<_> = verifier
+            .verify_reader(std::io::BufReader::new(FailingReader), "987654321")
+            .collect();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(TicketError::Io(_))));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_rate_limited() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_clock(Box::new(FixedClock(1_000)));
+        verifier.with_rate_limiter(RateLimiter::new(3, 60, 300).unwrap());
+        let mut payload = test_payload("987654321");
+        payload.iat = 1_000;
+        payload.exp = 1_060;
+        let forged = test_signer(2).sign(&payload);
+        let valid = signer.sign(&payload);
+
+        for _ in 0..3 {
+            assert!(matches!(
+                verifier.verify(&forged, "987654321"),
+                Err(TicketError::SignatureInvalid)
+            ));
+        }
+        // 冷却期内即使票据有效也直接拒绝
+        assert!(matches!(
+            verifier.verify(&valid, "987654321"),
+            Err(TicketError::RateLimited)
+        ));
+        payload.src_id = "555555555".to_owned();
+        assert!(verifier.verify(&signer.sign(&payload), "987654321").is_ok());
+        assert!(matches!(
+            verifier.verify_at(&valid, "987654321", 1_030),
+            Err(TicketError::RateLimited)
+        ));
+        // 冷却结束后恢复
+        let mut later = test_payload("987654321");
+        later.iat = 1_300;
+        later.exp = 1_400;
+        assert!(verifier
+            .verify_at(&signer.sign(&later), "987654321", 1_300)
+            .is_ok());
+    }
 
     #[test]
-    fn test_is_ticket() {
-        assert!(is_ticket(b"TICKET:v1:abc.def"));
-        assert!(!is_ticket(b"password123"));
-        assert!(!is_ticket(b""));
+    fn test_verify_audit() {
+        let signer = test_signer(1);
+        let mut verifier = test_verifier(&signer);
+        verifier.with_replay_cache(Arc::new(ReplayCache::new()));
+        let ticket = signer.sign(&test_payload("987654321"));
+
+        let report = verifier.verify_audit(&ticket, "987654321");
+        assert!(report.signature_ok && report.device_match && report.accepted);
+        assert!(!report.expired);
+        assert!(report.first_error.is_none());
+        // 审计不消耗 nonce
+        assert!(verifier.verify(&ticket, "987654321").is_ok());
+
+        let report = verifier.verify_audit(&ticket, "111111111");
+        assert!(report.signature_ok && !report.device_match && !report.accepted);
+        assert!(matches!(report.first_error, Some(TicketError::DeviceMismatch { .. })));
+
+        let mut payload = test_payload("987654321");
+        payload.exp = payload.iat - 3600;
+        let report = verifier.verify_audit(&signer.sign(&payload), "987654321");
+        assert!(report.expired && report.device_match && !report.accepted);
+
+        // 签名无效: 声明仍可展示, 但其余检查项不可信
+        let forged = test_signer(2).sign(&test_payload("987654321"));
+        let report = verifier.verify_audit(&forged, "987654321");
+        assert!(!report.signature_ok && !report.device_match && !report.accepted);
+        assert_eq!(report.decoded_claims.unwrap().src_id, "123456789");
+        assert!(matches!(report.first_error, Some(TicketError::SignatureInvalid)));
     }
 }
@@ -0,0 +1,150 @@
+//! 票据验证器构建器
+//!
+//! 适合在启动代码中一次性声明全部配置: 输入在 [`TicketVerifierBuilder::build`] 时统一校验,
+//! 有多处配置错误时一次性全部报告。
+
+use super::{ReplayStore, TicketError, TicketVerifier};
+use std::sync::Arc;
+
+/// [`TicketVerifier`] 构建器
+#[derive(Default)]
+pub struct TicketVerifierBuilder {
+    public_keys: Vec<String>,
+    clock_skew: Option<i64>,
+    max_lifetime: Option<i64>,
+    expected_audience: Option<String>,
+    prefix: Option<String>,
+    replay_cache: Option<Arc<dyn ReplayStore>>,
+}
+
+impl TicketVerifierBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置公钥 (十六进制), 替换之前添加的全部公钥
+    pub fn public_key_hex(mut self, hex_key: &str) -> Self {
+        self.public_keys = vec![hex_key.to_owned()];
+        self
+    }
+
+    /// 追加公钥 (十六进制), 用于密钥轮换
+    pub fn add_public_key_hex(mut self, hex_key: &str) -> Self {
+        self.public_keys.push(hex_key.to_owned());
+        self
+    }
+
+    /// 允许的时钟偏差 (秒)
+    pub fn clock_skew(mut self, seconds: i64) -> Self {
+        self.clock_skew = Some(seconds);
+        self
+    }
+
+    /// 票据最长有效期 (秒)
+    pub fn max_lifetime(mut self, seconds: i64) -> Self {
+        self.max_lifetime = Some(seconds);
+        self
+    }
+
+    /// 期望的受众
+    pub fn expected_audience(mut self, aud: &str) -> Self {
+        self.expected_audience = Some(aud.to_owned());
+        self
+    }
+
+    /// 票据前缀, 必须以版本标记结尾
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_owned());
+        self
+    }
+
+    /// 防重放缓存
+    pub fn replay_cache(mut self, cache: Arc<dyn ReplayStore>) -> Self {
+        self.replay_cache = Some(cache);
+        self
+    }
+
+    /// 校验全部配置并创建验证器
+    ///
+    /// 任一配置无效时返回 [`TicketError::ConfigurationError`], 其中列出所有问题。
+    pub fn build(self) -> Result<TicketVerifier, TicketError> {
+        let mut verifier = TicketVerifier::new();
+        let mut problems = Vec::new();
+        let mut check = |what: &str, result: Result<(), TicketError>| {
+            if let Err(e) = result {
+                match e {
+                    TicketError::ConfigurationError(msg) => problems.push(msg),
+                    e => problems.push(format!("{}: {}", what, e)),
+                }
+            }
+        };
+
+        for (i, key) in self.public_keys.iter().enumerate() {
+            check(&format!("公钥 #{}", i + 1), verifier.add_public_key_hex(key));
+        }
+        // 提供的公钥全部无效时已逐条报告, 这里只补充完全未提供公钥的情况
+        if self.public_keys.is_empty() {
+            check("公钥", verifier.ready_or_err());
+        }
+        if let Some(seconds) = self.clock_skew {
+            check("时钟偏差", verifier.with_clock_skew(seconds));
+        }
+        if let Some(seconds) = self.max_lifetime {
+            check("最长有效期", verifier.with_max_lifetime(seconds));
+        }
+        if let Some(prefix) = self.prefix.as_deref() {
+            check("票据前缀", verifier.with_prefix(prefix));
+        }
+        if let Some(aud) = self.expected_audience.as_deref() {
+            verifier.with_expected_audience(aud);
+        }
+        if let Some(cache) = self.replay_cache {
+            verifier.with_replay_cache(cache);
+        }
+
+        if problems.is_empty() {
+            Ok(verifier)
+        } else {
+            Err(TicketError::ConfigurationError(problems.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticket::TicketSigner;
+
+    #[test]
+    fn test_builder() {
+        let signer = TicketSigner::from_secret_hex(&hex::encode([1u8; 32])).unwrap();
+        let verifier = TicketVerifierBuilder::new()
+            .public_key_hex(&signer.verifying_key_hex())
+            .clock_skew(10)
+            .max_lifetime(600)
+            .expected_audience("rustdesk")
+            .prefix("TICKET:v1:")
+            .build()
+            .unwrap();
+        assert!(verifier.is_ticket(b"TICKET:v1:abc.def"));
+
+        // 所有问题一次性报告
+        let err = TicketVerifierBuilder::new()
+            .add_public_key_hex("zz")
+            .clock_skew(-1)
+            .max_lifetime(0)
+            .prefix("ACME:")
+            .build()
+            .err()
+            .unwrap();
+        let TicketError::ConfigurationError(msg) = err else {
+            panic!("unexpected error: {}", err);
+        };
+        assert_eq!(msg.split("; ").count(), 4, "{}", msg);
+
+        assert!(matches!(
+            TicketVerifierBuilder::new().build(),
+            Err(TicketError::ConfigurationError(_))
+        ));
+    }
+}
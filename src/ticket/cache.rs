@@ -0,0 +1,121 @@
+//! 验签结果缓存
+//!
+//! 重连风暴时同一张票据可能每秒被验证几十次, 缓存已通过验签的载荷,
+//! 命中时跳过 base64 解码、JSON 解析和 Ed25519 验签。
+//! 有效期、设备等声明检查每次仍会重新执行。
+
+use super::DecodedTicket;
+use std::{collections::HashMap, sync::Mutex};
+
+struct CacheEntry {
+    decoded: DecodedTicket,
+    /// 超过该时间戳后票据已无法通过验证
+    keep_until: i64,
+    /// 最近一次访问序号, 用于 LRU 淘汰
+    last_used: u64,
+}
+
+struct VerifyCacheInner {
+    entries: HashMap<Vec<u8>, CacheEntry>,
+    tick: u64,
+}
+
+/// 容量受限的 LRU 验签缓存, 以原始票据字节为键
+pub(super) struct VerifyCache {
+    capacity: usize,
+    inner: Mutex<VerifyCacheInner>,
+}
+
+impl VerifyCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(VerifyCacheInner {
+                entries: HashMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    pub(super) fn get(&self, ticket: &[u8], now: i64) -> Option<DecodedTicket> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        match inner.entries.get_mut(ticket) {
+            Some(entry) if entry.keep_until >= now => {
+                entry.last_used = tick;
+                Some(entry.decoded.clone())
+            }
+            Some(_) => {
+                inner.entries.remove(ticket);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(super) fn insert(&self, ticket: &[u8], decoded: &DecodedTicket, keep_until: i64, now: i64) {
+        if keep_until < now {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(ticket) {
+            inner.entries.retain(|_, entry| entry.keep_until >= now);
+            if inner.entries.len() >= self.capacity {
+                let lru = inner
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(lru) = lru {
+                    inner.entries.remove(&lru);
+                }
+            }
+        }
+        inner.tick += 1;
+        let last_used = inner.tick;
+        inner.entries.insert(
+            ticket.to_vec(),
+            CacheEntry {
+                decoded: decoded.clone(),
+                keep_until,
+                last_used,
+            },
+        );
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_cache_lru() {
+        let cache = VerifyCache::new(2);
+        let payload = DecodedTicket::default();
+        cache.insert(b"a", &payload, 100, 0);
+        cache.insert(b"b", &payload, 100, 0);
+        assert!(cache.get(b"a", 10).is_some());
+        // b 最久未使用, 被淘汰
+        cache.insert(b"c", &payload, 100, 10);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(b"b", 10).is_none());
+        assert!(cache.get(b"a", 10).is_some());
+        assert!(cache.get(b"c", 10).is_some());
+
+        // 过期条目不会命中
+        assert!(cache.get(b"a", 101).is_none());
+        assert_eq!(cache.len(), 1);
+        cache.insert(b"d", &payload, 50, 60);
+        assert!(cache.get(b"d", 60).is_none());
+    }
+}
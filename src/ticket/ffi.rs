@@ -0,0 +1,176 @@
+//! 票据验证的 C 接口, 供无法链接 Rust 类型的旧版 C/C++ 程序调用
+//!
+//! 全部缓冲区由调用方分配和释放, 本模块不持有、不分配任何调用方可见的内存。
+//!
+//! ```c
+//! /* 状态码 */
+//! #define RSC_TICKET_OK                 0  /* 验证成功, src_id 已写入 out_src_id_buf */
+//! #define RSC_TICKET_ERR_INVALID_ARG   -1  /* 指针为空 (且长度不为 0) 或设备 ID 为空 */
+//! #define RSC_TICKET_ERR_UTF8          -2  /* 设备 ID 或公钥不是合法 UTF-8 */
+//! #define RSC_TICKET_ERR_PUBLIC_KEY    -3  /* 公钥为空或无效 */
+//! #define RSC_TICKET_ERR_NOT_TICKET    -4  /* 输入不是票据, 调用方可继续普通密码验证 */
+//! #define RSC_TICKET_ERR_INVALID       -5  /* 是票据但验证失败, 应拒绝连接 */
+//! #define RSC_TICKET_ERR_BUFFER_SMALL  -6  /* 验证成功, 但缓冲区放不下 src_id 和结尾的 '\0' */
+//!
+//! int32_t rsc_ticket_verify(const uint8_t *ticket_ptr, size_t ticket_len,
+//!                           const uint8_t *device_id_ptr, size_t device_id_len,
+//!                           const uint8_t *pubkey_hex_ptr, size_t pubkey_hex_len,
+//!                           uint8_t *out_src_id_buf, size_t buf_len);
+//! ```
+
+use super::{authenticate_password, AuthResult, TicketError};
+
+pub const RSC_TICKET_OK: i32 = 0;
+pub const RSC_TICKET_ERR_INVALID_ARG: i32 = -1;
+pub const RSC_TICKET_ERR_UTF8: i32 = -2;
+pub const RSC_TICKET_ERR_PUBLIC_KEY: i32 = -3;
+pub const RSC_TICKET_ERR_NOT_TICKET: i32 = -4;
+pub const RSC_TICKET_ERR_INVALID: i32 = -5;
+pub const RSC_TICKET_ERR_BUFFER_SMALL: i32 = -6;
+
+/// 把 (指针, 长度) 转换为切片, 长度为 0 时允许空指针
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// 验证票据, 成功时把 src_id 以 `'\0'` 结尾写入 `out_src_id_buf`
+///
+/// 设备 ID 不能为空 (返回 `RSC_TICKET_ERR_INVALID_ARG`), 始终校验票据的目标设备。
+///
+/// # Safety
+///
+/// 每个指针在对应长度不为 0 时必须指向至少该长度的有效内存,
+/// `out_src_id_buf` 在 `buf_len` 不为 0 时必须可写 `buf_len` 字节;
+/// 调用期间这些内存不能被其他线程修改。
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn rsc_ticket_verify(
+    ticket_ptr: *const u8,
+    ticket_len: usize,
+    device_id_ptr: *const u8,
+    device_id_len: usize,
+    pubkey_hex_ptr: *const u8,
+    pubkey_hex_len: usize,
+    out_src_id_buf: *mut u8,
+    buf_len: usize,
+) -> i32 {
+    let (Some(ticket), Some(device_id), Some(pubkey_hex)) = (
+        input(ticket_ptr, ticket_len),
+        input(device_id_ptr, device_id_len),
+        input(pubkey_hex_ptr, pubkey_hex_len),
+    ) else {
+        return RSC_TICKET_ERR_INVALID_ARG;
+    };
+    if device_id.is_empty() || (buf_len != 0 && out_src_id_buf.is_null()) {
+        return RSC_TICKET_ERR_INVALID_ARG;
+    }
+    let (Ok(device_id), Ok(pubkey_hex)) =
+        (std::str::from_utf8(device_id), std::str::from_utf8(pubkey_hex))
+    else {
+        return RSC_TICKET_ERR_UTF8;
+    };
+
+    let payload = match authenticate_password(ticket, device_id, pubkey_hex) {
+        AuthResult::Ticket(payload) => payload,
+        AuthResult::NotTicket => return RSC_TICKET_ERR_NOT_TICKET,
+        AuthResult::TicketInvalid(
+            TicketError::MissingPublicKey
+            | TicketError::KeyHex(_)
+            | TicketError::BadKeyLength(_)
+            | TicketError::InvalidKey(_),
+        ) => return RSC_TICKET_ERR_PUBLIC_KEY,
+        AuthResult::TicketInvalid(_) => return RSC_TICKET_ERR_INVALID,
+    };
+
+    let src_id = payload.src_id.as_bytes();
+    if src_id.len() >= buf_len {
+        return RSC_TICKET_ERR_BUFFER_SMALL;
+    }
+    let out = std::slice::from_raw_parts_mut(out_src_id_buf, buf_len);
+    out[..src_id.len()].copy_from_slice(src_id);
+    out[src_id.len()] = 0;
+    RSC_TICKET_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticket::{TicketPayload, TicketSigner};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn verify(ticket: &str, device_id: &str, pubkey_hex: &str, buf: &mut [u8]) -> i32 {
+        unsafe {
+            rsc_ticket_verify(
+                ticket.as_ptr(),
+                ticket.len(),
+                device_id.as_ptr(),
+                device_id.len(),
+                pubkey_hex.as_ptr(),
+                pubkey_hex.len(),
+                buf.as_mut_ptr(),
+                buf.len(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_rsc_ticket_verify() {
+        let signer = TicketSigner::from_secret_hex(&hex::encode([1u8; 32])).unwrap();
+        let public_key = signer.verifying_key_hex();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let ticket = signer.sign(&TicketPayload {
+            src_id: "123456789".to_owned(),
+            dst_id: "987654321".into(),
+            exp: now + 60,
+            nonce: "0123456789abcdef".to_owned(),
+            iat: now,
+            ..Default::default()
+        });
+
+        let mut buf = [0xffu8; 16];
+        assert_eq!(verify(&ticket, "987654321", &public_key, &mut buf), RSC_TICKET_OK);
+        assert_eq!(&buf[..10], b"123456789\0");
+
+        let mut small = [0u8; 9];
+        assert_eq!(
+            verify(&ticket, "987654321", &public_key, &mut small),
+            RSC_TICKET_ERR_BUFFER_SMALL
+        );
+        assert_eq!(
+            verify(&ticket, "111111111", &public_key, &mut buf),
+            RSC_TICKET_ERR_INVALID
+        );
+        assert_eq!(
+            verify("hunter2", "987654321", &public_key, &mut buf),
+            RSC_TICKET_ERR_NOT_TICKET
+        );
+        assert_eq!(
+            verify(&ticket, "987654321", "abcd", &mut buf),
+            RSC_TICKET_ERR_PUBLIC_KEY
+        );
+        // 空设备 ID 不会跳过目标设备校验
+        assert_eq!(
+            verify(&ticket, "", &public_key, &mut buf),
+            RSC_TICKET_ERR_INVALID_ARG
+        );
+        let code = unsafe {
+            rsc_ticket_verify(
+                std::ptr::null(),
+                1,
+                std::ptr::null(),
+                0,
+                std::ptr::null(),
+                0,
+                buf.as_mut_ptr(),
+                0,
+            )
+        };
+        assert_eq!(code, RSC_TICKET_ERR_INVALID_ARG);
+    }
+}
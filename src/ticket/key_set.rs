@@ -0,0 +1,224 @@
+//! 从 API Server 获取公钥集合
+//!
+//! 文档格式: `{ "keys": [{ "kid": "2024-01", "key_hex": "...", "valid_until": 1700000000 }] }`,
+//! kid 和 valid_until (公钥停用时间, Unix 秒) 可省略。
+//! 获取结果按 URL 缓存在内存中: 缓存未过期时不发起请求, 两次请求之间至少间隔
+//! [`MIN_REFRESH_INTERVAL`]; 请求失败时继续使用上次成功获取的公钥, 避免 API Server
+//! 短暂不可用导致所有票据立即失效。
+//!
+//! 需启用 `key-fetch` feature; HTTP 请求复用 [`hbbs_http`](crate::hbbs_http) 的异步客户端,
+//! 只做离线验证的构建不包含这部分代码。
+
+use super::{parse_public_key_hex, TicketError, TicketKey, LOG_TARGET};
+use crate::hbbs_http::create_http_client_async_with_url;
+use hbb_common::{lazy_static, log};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// 缓存有效期
+const KEY_SET_TTL: Duration = Duration::from_secs(10 * 60);
+/// 两次请求之间的最短间隔
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// 请求超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct KeySetResponse {
+    keys: Vec<KeySetEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeySetEntry {
+    #[serde(default)]
+    kid: Option<String>,
+    key_hex: String,
+    #[serde(default)]
+    valid_until: Option<i64>,
+}
+
+#[derive(Default)]
+struct CachedKeySet {
+    keys: Option<Vec<TicketKey>>,
+    fetched_at: Option<Instant>,
+    last_attempt: Option<Instant>,
+}
+
+/// 缓存查询结果
+enum Lookup {
+    /// 缓存仍然有效
+    Fresh(Vec<TicketKey>),
+    /// 需要重新获取, 附带上次成功获取的公钥 (如有)
+    Refresh(Option<Vec<TicketKey>>),
+    /// 距上次请求太近, 只能使用上次的公钥 (如有)
+    Throttled(Option<Vec<TicketKey>>),
+}
+
+#[derive(Default)]
+struct KeySetCache {
+    entries: HashMap<String, CachedKeySet>,
+}
+
+impl KeySetCache {
+    /// 查询缓存; 返回 [`Lookup::Refresh`] 时同时记录本次请求时间
+    fn lookup(&mut self, url: &str, now: Instant) -> Lookup {
+        let entry = self.entries.entry(url.to_owned()).or_default();
+        if let (Some(keys), Some(fetched_at)) = (entry.keys.as_ref(), entry.fetched_at) {
+            if now.saturating_duration_since(fetched_at) < KEY_SET_TTL {
+                return Lookup::Fresh(keys.clone());
+            }
+        }
+        if let Some(last_attempt) = entry.last_attempt {
+            if now.saturating_duration_since(last_attempt) < MIN_REFRESH_INTERVAL {
+                return Lookup::Throttled(entry.keys.clone());
+            }
+        }
+        entry.last_attempt = Some(now);
+        Lookup::Refresh(entry.keys.clone())
+    }
+
+    fn store(&mut self, url: &str, keys: Vec<TicketKey>, now: Instant) {
+        let entry = self.entries.entry(url.to_owned()).or_default();
+        entry.keys = Some(keys);
+        entry.fetched_at = Some(now);
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref KEY_SET_CACHE: Mutex<KeySetCache> = Default::default();
+}
+
+/// 获取公钥集合, 优先使用缓存
+pub(super) async fn load_key_set(url: &str) -> Result<Vec<TicketKey>, TicketError> {
+    let last_known = match KEY_SET_CACHE.lock().unwrap().lookup(url, Instant::now()) {
+        Lookup::Fresh(keys) => return Ok(keys),
+        Lookup::Throttled(Some(keys)) => return Ok(keys),
+        Lookup::Throttled(None) => {
+            return Err(TicketError::KeyFetch("请求过于频繁, 稍后重试".to_owned()))
+        }
+        Lookup::Refresh(last_known) => last_known,
+    };
+
+    match fetch_key_set(url).await {
+        Ok(keys) => {
+            KEY_SET_CACHE
+                .lock()
+                .unwrap()
+                .store(url, keys.clone(), Instant::now());
+            Ok(keys)
+        }
+        Err(e) => match last_known {
+            Some(keys) => {
+                log::warn!(target: LOG_TARGET, "获取票据公钥集合失败, 继续使用上次的公钥: {}", e);
+                Ok(keys)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+async fn fetch_key_set(url: &str) -> Result<Vec<TicketKey>, TicketError> {
+    log::debug!(target: LOG_TARGET, "开始获取票据公钥集合: {}", url);
+    let client = create_http_client_async_with_url(url).await;
+    let resp = client
+        .get(url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| TicketError::KeyFetch(e.to_string()))?;
+    let key_set = resp
+        .json::<KeySetResponse>()
+        .await
+        .map_err(|e| TicketError::KeyFetch(e.to_string()))?;
+    parse_key_set(key_set)
+}
+
+fn parse_key_set(key_set: KeySetResponse) -> Result<Vec<TicketKey>, TicketError> {
+    if key_set.keys.is_empty() {
+        return Err(TicketError::KeyFetch("公钥集合为空".to_owned()));
+    }
+    key_set
+        .keys
+        .into_iter()
+        .map(|entry| {
+            let key = parse_public_key_hex(&entry.key_hex)?;
+            let mut key = TicketKey::ed25519(entry.kid, key);
+            key.valid_until = entry.valid_until;
+            Ok(key)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_set(json: &str) -> Result<Vec<TicketKey>, TicketError> {
+        parse_key_set(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_parse_key_set() {
+        let key_hex = hex::encode(
+            ed25519_dalek::SigningKey::from_bytes(&[1u8; 32])
+                .verifying_key()
+                .as_bytes(),
+        );
+        let keys = key_set(&format!(
+            r#"{{"keys":[{{"kid":"a","key_hex":"{0}","valid_until":100}},{{"key_hex":"{0}"}}]}}"#,
+            key_hex
+        ))
+        .unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].kid.as_deref(), Some("a"));
+        assert!(keys[1].kid.is_none());
+        assert_eq!(keys[0].valid_until, Some(100));
+        assert!(keys[1].valid_until.is_none());
+
+        assert!(matches!(
+            key_set(r#"{"keys":[]}"#),
+            Err(TicketError::KeyFetch(_))
+        ));
+        assert!(matches!(
+            key_set(r#"{"keys":[{"key_hex":"zz"}]}"#),
+            Err(TicketError::KeyHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_set_cache() {
+        let key = parse_public_key_hex(&hex::encode(
+            ed25519_dalek::SigningKey::from_bytes(&[1u8; 32])
+                .verifying_key()
+                .as_bytes(),
+        ))
+        .unwrap();
+        let mut cache = KeySetCache::default();
+        let t0 = Instant::now();
+        let url = "https://api.example.com/keys";
+
+        assert!(matches!(cache.lookup(url, t0), Lookup::Refresh(None)));
+        // 首次请求失败后, 最短间隔内不再请求
+        assert!(matches!(
+            cache.lookup(url, t0 + Duration::from_secs(1)),
+            Lookup::Throttled(None)
+        ));
+
+        let t1 = t0 + MIN_REFRESH_INTERVAL;
+        assert!(matches!(cache.lookup(url, t1), Lookup::Refresh(None)));
+        cache.store(url, vec![TicketKey::ed25519(None, key)], t1);
+        assert!(matches!(
+            cache.lookup(url, t1 + Duration::from_secs(60)),
+            Lookup::Fresh(_)
+        ));
+
+        // 过期后重新获取, 并带上上次的公钥用于失败回退
+        let t2 = t1 + KEY_SET_TTL;
+        assert!(matches!(cache.lookup(url, t2), Lookup::Refresh(Some(_))));
+        assert!(matches!(cache.lookup(url, t2), Lookup::Throttled(Some(_))));
+    }
+}
@@ -0,0 +1,129 @@
+//! 按主控端 ID 限制验证失败次数
+//!
+//! 同一 src_id 在时间窗口内验证失败达到上限后, 冷却期内该 src_id 的票据直接以
+//! [`TicketError::RateLimited`](super::TicketError::RateLimited) 拒绝, 不再解码验签。
+//!
+//! 失败票据的 src_id 未经验签, 任何人都可以冒用; 这只是减轻无效票据刷验证的软性措施,
+//! 不是认证手段。冒用他人 src_id 的攻击者也能让该 src_id 暂时无法连接, 阈值不宜设得过低。
+
+use super::TicketError;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// 记录数超过该值时清理已失效的记录, 防止伪造大量 src_id 撑大内存
+const PRUNE_THRESHOLD: usize = 4096;
+
+struct FailureRecord {
+    window_start: i64,
+    failures: u32,
+    blocked_until: Option<i64>,
+}
+
+struct RateLimiterInner {
+    max_failures: u32,
+    window_secs: i64,
+    cooldown_secs: i64,
+    records: Mutex<HashMap<String, FailureRecord>>,
+}
+
+/// 验证失败限流器
+///
+/// 克隆得到的句柄共享同一份计数, 可以分发给多个验证器或工作线程。
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<RateLimiterInner>,
+}
+
+impl RateLimiter {
+    /// `window_secs` 秒内失败 `max_failures` 次后, 拒绝该 src_id `cooldown_secs` 秒
+    pub fn new(
+        max_failures: u32,
+        window_secs: i64,
+        cooldown_secs: i64,
+    ) -> Result<Self, TicketError> {
+        if max_failures == 0 || window_secs <= 0 || cooldown_secs <= 0 {
+            return Err(TicketError::ConfigurationError(format!(
+                "限流参数必须为正数: 次数 {}, 窗口 {} 秒, 冷却 {} 秒",
+                max_failures, window_secs, cooldown_secs
+            )));
+        }
+        Ok(Self {
+            inner: Arc::new(RateLimiterInner {
+                max_failures,
+                window_secs,
+                cooldown_secs,
+                records: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// 该 src_id 当前是否处于冷却期
+    pub fn is_limited(&self, src_id: &str, now: i64) -> bool {
+        let records = self.inner.records.lock().unwrap();
+        records
+            .get(src_id)
+            .and_then(|r| r.blocked_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// 记录一次验证失败
+    pub(super) fn record_failure(&self, src_id: &str, now: i64) {
+        let inner = &self.inner;
+        let mut records = inner.records.lock().unwrap();
+        if records.len() >= PRUNE_THRESHOLD {
+            records.retain(|_, r| {
+                r.blocked_until.is_some_and(|until| now < until)
+                    || now.saturating_sub(r.window_start) < inner.window_secs
+            });
+        }
+        let record = records.entry(src_id.to_owned()).or_insert(FailureRecord {
+            window_start: now,
+            failures: 0,
+            blocked_until: None,
+        });
+        if now.saturating_sub(record.window_start) >= inner.window_secs {
+            record.window_start = now;
+            record.failures = 0;
+        }
+        record.failures += 1;
+        if record.failures >= inner.max_failures {
+            record.blocked_until = Some(now.saturating_add(inner.cooldown_secs));
+            record.window_start = now;
+            record.failures = 0;
+        }
+    }
+
+    /// 验证成功后清除该 src_id 的失败记录
+    pub(super) fn record_success(&self, src_id: &str) {
+        self.inner.records.lock().unwrap().remove(src_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(3, 60, 300).unwrap();
+        limiter.record_failure("a", 0);
+        limiter.record_failure("a", 10);
+        assert!(!limiter.is_limited("a", 10));
+        limiter.record_failure("a", 20);
+        assert!(limiter.is_limited("a", 20));
+        assert!(limiter.is_limited("a", 319));
+        assert!(!limiter.is_limited("a", 320));
+        assert!(!limiter.is_limited("b", 20));
+
+        // 窗口外的失败重新计数
+        limiter.record_failure("b", 0);
+        limiter.record_failure("b", 30);
+        limiter.record_failure("b", 61);
+        assert!(!limiter.is_limited("b", 61));
+
+        assert!(RateLimiter::new(0, 60, 300).is_err());
+        assert!(RateLimiter::new(3, 0, 300).is_err());
+    }
+}
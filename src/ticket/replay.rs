@@ -0,0 +1,98 @@
+//! 票据防重放缓存
+//!
+//! 记录已经使用过的票据 nonce, 在票据有效期内拒绝再次使用同一 nonce。
+
+use std::{collections::HashMap, sync::Mutex};
+
+/// 两次主动清理过期记录之间的最小间隔 (秒)
+pub(super) const EVICT_INTERVAL_SECS: i64 = 60;
+/// 记录数超过该值时无视间隔立即清理
+const EVICT_THRESHOLD: usize = 4096;
+
+/// 防重放存储
+///
+/// 嵌入方可以实现该 trait 提供自己的存储 (例如跨进程共享)。
+pub trait ReplayStore: Send + Sync {
+    /// 登记 nonce
+    ///
+    /// - `keep_until`: 超过该时间戳后票据本身已无法通过验证, 记录可以被清理
+    /// - `now`: 当前 Unix 秒
+    ///
+    /// 返回 `true` 表示首次出现并已登记, `false` 表示 nonce 已被使用过
+    fn check_and_insert(&self, nonce: &str, keep_until: i64, now: i64) -> bool;
+}
+
+struct ReplayCacheInner {
+    /// nonce -> 记录保留截止时间
+    seen: HashMap<String, i64>,
+    last_evict: i64,
+}
+
+/// 基于内存 `HashMap` 的默认防重放缓存
+pub struct ReplayCache {
+    inner: Mutex<ReplayCacheInner>,
+}
+
+impl ReplayCache {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(ReplayCacheInner {
+                seen: HashMap::new(),
+                last_evict: 0,
+            }),
+        }
+    }
+
+    /// 当前记录的 nonce 数量 (可能包含尚未清理的过期记录)
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayStore for ReplayCache {
+    fn check_and_insert(&self, nonce: &str, keep_until: i64, now: i64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if now.saturating_sub(inner.last_evict) >= EVICT_INTERVAL_SECS
+            || inner.seen.len() >= EVICT_THRESHOLD
+        {
+            inner.seen.retain(|_, until| *until >= now);
+            inner.last_evict = now;
+        }
+        match inner.seen.get(nonce) {
+            Some(until) if *until >= now => false,
+            _ => {
+                inner.seen.insert(nonce.to_owned(), keep_until);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_cache() {
+        let cache = ReplayCache::new();
+        assert!(cache.check_and_insert("a", 100, 10));
+        assert!(!cache.check_and_insert("a", 100, 20));
+        assert!(cache.check_and_insert("b", 50, 20));
+        // 过期记录不再阻止同一 nonce, 并在清理时被移除
+        assert!(cache.check_and_insert("b", 200, 120));
+        assert_eq!(cache.len(), 1);
+        // 未到清理间隔时不清理
+        assert!(cache.check_and_insert("c", 300, 150));
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,73 @@
+//! 票据吊销列表
+//!
+//! 设备确认泄露后, 运维可以在票据自然过期前按 nonce 或主控端 ID 拒绝其票据。
+
+use super::TicketPayload;
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+#[derive(Default)]
+struct RevocationInner {
+    nonces: HashSet<String>,
+    src_ids: HashSet<String>,
+}
+
+/// 吊销列表
+///
+/// 克隆得到的句柄共享同一份数据, 可以在不重建验证器的情况下实时更新。
+#[derive(Clone, Default)]
+pub struct RevocationList {
+    inner: Arc<RwLock<RevocationInner>>,
+}
+
+impl RevocationList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 吊销指定 nonce 的票据
+    pub fn revoke_nonce(&self, nonce: &str) {
+        self.inner.write().unwrap().nonces.insert(nonce.to_owned());
+    }
+
+    /// 吊销指定主控端设备签发的全部票据
+    pub fn revoke_src(&self, src_id: &str) {
+        self.inner.write().unwrap().src_ids.insert(src_id.to_owned());
+    }
+
+    /// 票据是否已被吊销
+    pub fn is_revoked(&self, payload: &TicketPayload) -> bool {
+        let inner = self.inner.read().unwrap();
+        inner.nonces.contains(&payload.nonce) || inner.src_ids.contains(&payload.src_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revocation_list() {
+        let list = RevocationList::new();
+        let shared = list.clone();
+        let payload = TicketPayload {
+            src_id: "123456789".to_owned(),
+            nonce: "0123456789abcdef".to_owned(),
+            ..Default::default()
+        };
+        assert!(!list.is_revoked(&payload));
+        shared.revoke_nonce("0123456789abcdef");
+        assert!(list.is_revoked(&payload));
+
+        let other = TicketPayload {
+            src_id: "123456789".to_owned(),
+            nonce: "fedcba9876543210".to_owned(),
+            ..Default::default()
+        };
+        assert!(!list.is_revoked(&other));
+        shared.revoke_src("123456789");
+        assert!(list.is_revoked(&other));
+    }
+}
@@ -0,0 +1,79 @@
+//! 签名算法
+//!
+//! 验证器通过 [`SignatureScheme`] 验签, 默认使用 Ed25519。
+//! 需要其他算法 (如受 HSM 限制只能使用 P-256 ECDSA) 时自行实现该 trait,
+//! 再通过 [`TicketVerifier::add_public_key_scheme`](super::TicketVerifier::add_public_key_scheme) 加载。
+//!
+//! Ed25519 公钥同时接受 Ed25519ph ([`ALG_ED25519PH`], RFC 8032 预哈希变体, 不带 context):
+//! 签名覆盖的是载荷的 SHA-512, 签发方处理大载荷时不必两次遍历原始字节。
+
+use ed25519_dalek::{Digest, Sha512, Signature, Verifier, VerifyingKey};
+
+/// Ed25519 的算法标识, 载荷未携带 alg 时视为该算法
+pub const ALG_ED25519: &str = "EdDSA";
+/// Ed25519ph 的算法标识, 使用同一个 Ed25519 公钥验签
+pub const ALG_ED25519PH: &str = "Ed25519ph";
+
+/// 签名算法
+pub trait SignatureScheme: Send + Sync {
+    /// 算法标识, 与票据载荷中的 alg 字段对应
+    fn algorithm(&self) -> &str;
+
+    /// 验证签名, 签名格式由具体算法决定
+    #[allow(clippy::result_unit_err)]
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<(), ()>;
+
+    /// 按票据声明的算法验证签名, 默认只接受 [`algorithm`](Self::algorithm)
+    ///
+    /// 同一公钥支持多种算法变体时重写该方法。
+    #[allow(clippy::result_unit_err)]
+    fn verify_with(&self, alg: &str, msg: &[u8], sig: &[u8]) -> Result<(), ()> {
+        if alg == self.algorithm() {
+            self.verify(msg, sig)
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// Ed25519 签名算法
+pub struct Ed25519Scheme {
+    key: VerifyingKey,
+}
+
+impl Ed25519Scheme {
+    /// 使用公钥创建
+    pub fn new(key: VerifyingKey) -> Self {
+        Self { key }
+    }
+}
+
+impl SignatureScheme for Ed25519Scheme {
+    fn algorithm(&self) -> &str {
+        ALG_ED25519
+    }
+
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<(), ()> {
+        let sig: [u8; 64] = sig.try_into().map_err(|_| ())?;
+        self.key
+            .verify(msg, &Signature::from_bytes(&sig))
+            .map_err(|_| ())
+    }
+
+    fn verify_with(&self, alg: &str, msg: &[u8], sig: &[u8]) -> Result<(), ()> {
+        match alg {
+            ALG_ED25519 => self.verify(msg, sig),
+            ALG_ED25519PH => {
+                let sig: [u8; 64] = sig.try_into().map_err(|_| ())?;
+                self.key
+                    .verify_prehashed(
+                        Sha512::new().chain_update(msg),
+                        None,
+                        &Signature::from_bytes(&sig),
+                    )
+                    .map_err(|_| ())
+            }
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! 票据签发
+//!
+//! 生成与 [`TicketVerifier`](super::TicketVerifier) 配套的票据, 用于测试和开发工具。
+//! 生产环境的票据由 API Server 签发。
+
+use super::{
+    b64_encode, canonical_json, delegation_message, encode_binary, Delegation, TicketError,
+    TicketPayload, TicketVersion, ALG_ED25519PH, COMPRESSED_MARKER, TICKET_PREFIX,
+    TICKET_PREFIX_V2,
+};
+use ed25519_dalek::{Digest, Sha512, Signer, SigningKey};
+use flate2::{write::DeflateEncoder, Compression};
+use hbb_common::rand::{rngs::OsRng, RngCore};
+use std::borrow::Cow;
+use zeroize::Zeroizing;
+
+/// 默认 nonce 的随机字节数 (十六进制编码后 32 个字符)
+const NONCE_BYTES: usize = 16;
+
+type NonceFn = Box<dyn Fn() -> String + Send + Sync>;
+
+/// 票据签发器
+///
+/// 私钥种子保存在 [`Zeroizing`] 中, 签发器释放时清零, 不会残留在已释放的内存里;
+/// 每次签名临时构造的 [`SigningKey`] 在使用后同样清零。
+///
+/// 载荷的 nonce 为空时, 签发器在签名前自动生成一个, 默认为 16 字节随机数的十六进制,
+/// 可用 [`with_nonce_fn`](Self::with_nonce_fn) 替换。
+pub struct TicketSigner {
+    secret: Zeroizing<[u8; 32]>,
+    nonce_fn: NonceFn,
+}
+
+impl TicketSigner {
+    /// 使用十六进制编码的 32 字节私钥种子创建签发器
+    pub fn from_secret_hex(hex_key: &str) -> Result<Self, TicketError> {
+        let key_bytes = Zeroizing::new(hex::decode(hex_key).map_err(TicketError::KeyHex)?);
+        if key_bytes.len() != 32 {
+            return Err(TicketError::BadKeyLength(key_bytes.len()));
+        }
+        let mut secret = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&key_bytes);
+        Ok(Self::with_secret(secret))
+    }
+
+    /// 随机生成新的密钥对
+    pub fn generate() -> Self {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(secret.as_mut());
+        Self::with_secret(secret)
+    }
+
+    fn with_secret(secret: Zeroizing<[u8; 32]>) -> Self {
+        Self {
+            secret,
+            nonce_fn: Box::new(random_nonce),
+        }
+    }
+
+    /// 替换自动生成 nonce 的函数, 只用于载荷 nonce 为空的情况
+    ///
+    /// 测试可以用确定的 nonce 得到可复现的票据; 生产签发方可以使用可排序的唯一值
+    /// (如 UUIDv7)。生成的值必须在票据有效期内唯一, 且满足验证器的 nonce 最小长度
+    /// (默认 16 个字符)。
+    pub fn with_nonce_fn(&mut self, f: impl Fn() -> String + Send + Sync + 'static) {
+        self.nonce_fn = Box::new(f);
+    }
+
+    /// nonce 为空时补上新生成的 nonce
+    fn with_nonce<'a>(&self, payload: &'a TicketPayload) -> Cow<'a, TicketPayload> {
+        if !payload.nonce.is_empty() {
+            return Cow::Borrowed(payload);
+        }
+        Cow::Owned(TicketPayload {
+            nonce: (self.nonce_fn)(),
+            ..payload.clone()
+        })
+    }
+
+    /// 导出十六进制编码的私钥种子, 可再用 [`from_secret_hex`](Self::from_secret_hex) 还原
+    ///
+    /// **私钥泄露等同于任何人都能签发免密连接票据。** 只应在生成密钥后写入 API Server
+    /// 的密钥文件或密钥管理系统时调用一次, 不要打印到终端或日志;
+    /// 返回值在释放时清零, 调用方不要把内容复制到普通 `String` 中。
+    pub fn secret_hex(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(self.secret.as_slice()))
+    }
+
+    fn signing_key(&self) -> SigningKey {
+        SigningKey::from_bytes(&self.secret)
+    }
+
+    /// 十六进制编码的公钥, 可直接传给 [`TicketVerifier::set_public_key_hex`](super::TicketVerifier::set_public_key_hex)
+    pub fn verifying_key_hex(&self) -> String {
+        hex::encode(self.signing_key().verifying_key().as_bytes())
+    }
+
+    /// 签发票据: `TICKET:v1:<base64url(payload)>.<base64url(signature)>`
+    ///
+    /// 载荷按 [`canonical_json`] 序列化, 签名覆盖的是序列化后的原始载荷字节
+    pub fn sign(&self, payload: &TicketPayload) -> String {
+        let payload_bytes = canonical_json(&self.with_nonce(payload));
+        self.assemble(TICKET_PREFIX, &payload_bytes)
+    }
+
+    /// 签发 v2 票据: 载荷使用 CBOR 编码, 长度明显短于 v1
+    ///
+    /// 签名覆盖的是 CBOR 编码后的原始载荷字节
+    pub fn sign_v2(&self, payload: &TicketPayload) -> String {
+        let mut payload_bytes = Vec::new();
+        ciborium::into_writer(&*self.with_nonce(payload), &mut payload_bytes)
+            .expect("票据载荷序列化失败");
+        self.assemble(TICKET_PREFIX_V2, &payload_bytes)
+    }
+
+    /// 签发二进制帧格式的 v2 票据 (见 [`encode_binary`]), 适合写入二维码
+    ///
+    /// CBOR 载荷超过 65535 字节时返回 [`TicketError::PayloadTooLarge`]
+    pub fn sign_binary(&self, payload: &TicketPayload) -> Result<Vec<u8>, TicketError> {
+        let mut payload_bytes = Vec::new();
+        ciborium::into_writer(&*self.with_nonce(payload), &mut payload_bytes)
+            .expect("票据载荷序列化失败");
+        let signature = self.signing_key().sign(&payload_bytes);
+        encode_binary(TicketVersion::V2, &payload_bytes, &signature.to_bytes())
+    }
+
+    /// 签发 DEFLATE 压缩的 v2 票据: `TICKET:v2:~<base64url(deflate(cbor))>.<base64url(signature)>`
+    ///
+    /// 适合携带较长 scopes 列表的载荷; 签名覆盖的是压缩后的字节, 验证器验签通过后才解压
+    pub fn sign_v2_compressed(&self, payload: &TicketPayload) -> String {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        ciborium::into_writer(&*self.with_nonce(payload), &mut encoder)
+            .expect("票据载荷序列化失败");
+        let payload_bytes = encoder.finish().expect("票据载荷压缩失败");
+        self.assemble(
+            &format!("{}{}", TICKET_PREFIX_V2, COMPRESSED_MARKER),
+            &payload_bytes,
+        )
+    }
+
+    /// 签发 Ed25519ph 签名的 v2 票据: 载荷的 alg 设为 [`ALG_ED25519PH`],
+    /// 签名覆盖 CBOR 载荷的 SHA-512, 验证器使用同一个 Ed25519 公钥
+    pub fn sign_v2_prehashed(&self, payload: &TicketPayload) -> String {
+        let payload = TicketPayload {
+            alg: Some(ALG_ED25519PH.to_owned()),
+            ..self.with_nonce(payload).into_owned()
+        };
+        let mut payload_bytes = Vec::new();
+        ciborium::into_writer(&payload, &mut payload_bytes).expect("票据载荷序列化失败");
+        let signature = self
+            .signing_key()
+            .sign_prehashed(Sha512::new().chain_update(&payload_bytes), None)
+            .expect("Ed25519ph 签名失败");
+        format!(
+            "{}{}.{}",
+            TICKET_PREFIX_V2,
+            b64_encode(&payload_bytes),
+            b64_encode(&signature.to_bytes())
+        )
+    }
+
+    /// 以本签发器为信任锚, 为 `subkey` 签发有效期到 `exp` 的委托凭据
+    ///
+    /// 之后由 `subkey` 签发载荷 dlg 为该凭据的票据, 见 [`Delegation`]。
+    pub fn delegate(&self, subkey: &TicketSigner, exp: i64) -> Delegation {
+        let key = subkey.signing_key().verifying_key().to_bytes();
+        let signature = self.signing_key().sign(&delegation_message(&key, exp));
+        Delegation {
+            key: hex::encode(key),
+            exp,
+            sig: b64_encode(&signature.to_bytes()),
+        }
+    }
+
+    fn assemble(&self, prefix: &str, payload_bytes: &[u8]) -> String {
+        let signature = self.signing_key().sign(payload_bytes);
+        format!(
+            "{}{}.{}",
+            prefix,
+            b64_encode(payload_bytes),
+            b64_encode(&signature.to_bytes())
+        )
+    }
+}
+
+/// 默认 nonce: [`NONCE_BYTES`] 字节随机数的十六进制
+fn random_nonce() -> String {
+    let mut bytes = [0u8; NONCE_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TicketVerifier;
+    use super::*;
+
+    #[test]
+    fn test_sign_round_trip() {
+        let signer = TicketSigner::generate();
+        let mut verifier = TicketVerifier::new();
+        verifier
+            .set_public_key_hex(&signer.verifying_key_hex())
+            .unwrap();
+        let payload = TicketPayload {
+            src_id: "123456789".to_owned(),
+            dst_id: "987654321".into(),
+            exp: i64::MAX / 2,
+            nonce: "0123456789abcdef".to_owned(),
+            ..Default::default()
+        };
+        let ticket = signer.sign(&payload);
+        assert!(ticket.starts_with(TICKET_PREFIX));
+        let verified = verifier.verify(&ticket, "987654321").unwrap();
+        assert_eq!(verified.src_id, payload.src_id);
+    }
+
+    #[test]
+    fn test_nonce_fn() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let payload = TicketPayload {
+            src_id: "123456789".to_owned(),
+            dst_id: "987654321".into(),
+            exp: 1_060,
+            iat: 1_000,
+            ..Default::default()
+        };
+        let deterministic = || {
+            let mut signer = TicketSigner::from_secret_hex(&"01".repeat(32)).unwrap();
+            let counter = AtomicU64::new(0);
+            signer.with_nonce_fn(move || {
+                format!("test-nonce-{:06}", counter.fetch_add(1, Ordering::Relaxed))
+            });
+            signer
+        };
+
+        // 相同的 nonce 序列得到相同的票据
+        let (a, b) = (deterministic(), deterministic());
+        assert_eq!(a.sign(&payload), b.sign(&payload));
+        let ticket = a.sign(&payload);
+        let claims = super::super::decode_payload_unverified(&ticket).unwrap();
+        assert_eq!(claims.nonce, "test-nonce-000001");
+
+        // 已有 nonce 时保持不变
+        let fixed = TicketPayload {
+            nonce: "0123456789abcdef".to_owned(),
+            ..payload.clone()
+        };
+        let claims = super::super::decode_payload_unverified(&a.sign_v2(&fixed)).unwrap();
+        assert_eq!(claims.nonce, "0123456789abcdef");
+
+        // 默认随机 nonce
+        let claims =
+            super::super::decode_payload_unverified(&TicketSigner::generate().sign(&payload))
+                .unwrap();
+        assert_eq!(claims.nonce.len(), NONCE_BYTES * 2);
+    }
+
+    #[test]
+    fn test_secret_hex_round_trip() {
+        let signer = TicketSigner::generate();
+        let restored = TicketSigner::from_secret_hex(&signer.secret_hex()).unwrap();
+        assert_eq!(signer.verifying_key_hex(), restored.verifying_key_hex());
+    }
+
+    #[test]
+    fn test_from_secret_hex() {
+        let signer = TicketSigner::from_secret_hex(&"01".repeat(32)).unwrap();
+        let same = TicketSigner::from_secret_hex(&"01".repeat(32)).unwrap();
+        assert_eq!(signer.verifying_key_hex(), same.verifying_key_hex());
+        assert!(matches!(
+            TicketSigner::from_secret_hex("0102"),
+            Err(TicketError::BadKeyLength(2))
+        ));
+        assert!(matches!(
+            TicketSigner::from_secret_hex("xyz"),
+            Err(TicketError::KeyHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_secret_zeroized_on_drop() {
+        fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>(_: &T) {}
+
+        let signer = TicketSigner::from_secret_hex(&"01".repeat(32)).unwrap();
+        assert_zeroize_on_drop(&signer.secret);
+        // 释放前可以正常签名
+        assert!(signer
+            .sign(&TicketPayload::default())
+            .starts_with(TICKET_PREFIX));
+    }
+}
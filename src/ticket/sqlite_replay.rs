@@ -0,0 +1,130 @@
+//! 基于 SQLite 的防重放存储
+//!
+//! 内存中的 [`ReplayCache`](super::ReplayCache) 在进程重启后清空, 有效期内截获的一次性票据
+//! 可以在客户端崩溃重启后再次使用; 该存储把已使用的 nonce 及其保留截止时间写入数据库文件,
+//! 重启后重新打开同一文件即可继续拒绝。需要启用 `rusqlite` feature。
+
+use super::{replay::EVICT_INTERVAL_SECS, unix_time, ReplayStore, LOG_TARGET};
+use hbb_common::log;
+use rusqlite::{params, Connection};
+use std::{path::Path, sync::Mutex, time::Duration, time::SystemTime};
+
+/// 数据库被其他进程锁定时的最长等待时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct SqliteReplayInner {
+    conn: Connection,
+    last_evict: i64,
+}
+
+/// 持久化到 SQLite 数据库文件的防重放存储
+pub struct SqliteReplayStore {
+    inner: Mutex<SqliteReplayInner>,
+}
+
+impl SqliteReplayStore {
+    /// 打开 (不存在时创建) 数据库文件, 并清理已过期的记录
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS ticket_nonces (
+                nonce TEXT PRIMARY KEY NOT NULL,
+                keep_until INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        let now = unix_time(SystemTime::now());
+        conn.execute(
+            "DELETE FROM ticket_nonces WHERE keep_until < ?1",
+            params![now],
+        )?;
+        Ok(Self {
+            inner: Mutex::new(SqliteReplayInner {
+                conn,
+                last_evict: now,
+            }),
+        })
+    }
+
+    /// 当前记录的 nonce 数量 (可能包含尚未清理的过期记录)
+    pub fn len(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .conn
+            .query_row("SELECT COUNT(*) FROM ticket_nonces", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map_or(0, |count| count as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn try_check_and_insert(
+        inner: &mut SqliteReplayInner,
+        nonce: &str,
+        keep_until: i64,
+        now: i64,
+    ) -> rusqlite::Result<bool> {
+        if now.saturating_sub(inner.last_evict) >= EVICT_INTERVAL_SECS {
+            inner.conn.execute(
+                "DELETE FROM ticket_nonces WHERE keep_until < ?1",
+                params![now],
+            )?;
+            inner.last_evict = now;
+        }
+        // 新 nonce 直接插入; 已存在但记录已过期时覆盖; 仍在有效期内时不修改任何行
+        let changed = inner.conn.execute(
+            "INSERT INTO ticket_nonces (nonce, keep_until) VALUES (?1, ?2)
+             ON CONFLICT (nonce) DO UPDATE SET keep_until = excluded.keep_until
+             WHERE ticket_nonces.keep_until < ?3",
+            params![nonce, keep_until, now],
+        )?;
+        Ok(changed == 1)
+    }
+}
+
+impl ReplayStore for SqliteReplayStore {
+    fn check_and_insert(&self, nonce: &str, keep_until: i64, now: i64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match Self::try_check_and_insert(&mut inner, nonce, keep_until, now) {
+            Ok(first_use) => first_use,
+            Err(e) => {
+                // 无法确认 nonce 未被使用时按已使用处理
+                log::error!(target: LOG_TARGET, "写入防重放数据库失败, 拒绝票据: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_survives_restart() {
+        let path =
+            std::env::temp_dir().join(format!("ticket_replay_test_{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let now = unix_time(SystemTime::now());
+
+        let store = SqliteReplayStore::open(&path).unwrap();
+        assert!(store.check_and_insert("a", now + 600, now));
+        assert!(!store.check_and_insert("a", now + 600, now));
+        assert!(store.check_and_insert("expired", now - 1, now - 10));
+        drop(store);
+
+        // 重新打开模拟进程重启: 未过期的 nonce 仍被拒绝, 过期记录在打开时被清理
+        let store = SqliteReplayStore::open(&path).unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(!store.check_and_insert("a", now + 600, now));
+        assert!(store.check_and_insert("b", now + 600, now));
+        // 记录过期后同一 nonce 可以重新登记
+        assert!(store.check_and_insert("a", now + 1200, now + 601));
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
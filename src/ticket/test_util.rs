@@ -0,0 +1,106 @@
+//! 测试用票据样例 (需启用 `test-util` feature)
+//!
+//! 依赖本 crate 的下游 crate 在集成测试中用这些函数构造有效和无效的票据,
+//! 覆盖自身的票据处理分支, 无需重新实现签名。
+//!
+//! 样例密钥对固定不变, 同一公钥在每次运行中都相同; nonce 按调用顺序递增,
+//! 启用防重放缓存的验证器也能逐张接受。**样例私钥是公开的, 不得用于生产环境。**
+
+use super::{unix_time, TicketPayload, TicketSigner, TicketVerifier};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+/// 样例私钥种子 (十六进制)
+pub const FIXTURE_SECRET_HEX: &str =
+    "7465737420666978747572652064657465726d696e6973746963206b65792121";
+/// 样例公钥 (十六进制), 与 [`FIXTURE_SECRET_HEX`] 对应
+pub const FIXTURE_PUBLIC_KEY_HEX: &str =
+    "097b66390cb0efc58cae7471f0e02508ed1a98ff8b9c6c3e32b2c88608123469";
+
+static NEXT_NONCE: AtomicU64 = AtomicU64::new(0);
+
+/// 使用样例私钥的签发器
+pub fn signer() -> TicketSigner {
+    TicketSigner::from_secret_hex(FIXTURE_SECRET_HEX).expect("样例私钥无效")
+}
+
+/// 已配置样例公钥、其余均为默认设置的验证器
+pub fn verifier() -> TicketVerifier {
+    let mut verifier = TicketVerifier::new();
+    verifier
+        .set_public_key_hex(FIXTURE_PUBLIC_KEY_HEX)
+        .expect("样例公钥无效");
+    verifier
+}
+
+/// 样例载荷: 签发于 `iat`, 有效期 `ttl` 秒
+pub fn payload(src_id: &str, dst_id: &str, iat: i64, ttl: i64) -> TicketPayload {
+    TicketPayload {
+        src_id: src_id.to_owned(),
+        dst_id: dst_id.into(),
+        exp: iat.saturating_add(ttl),
+        nonce: format!(
+            "fixture-{:016x}",
+            NEXT_NONCE.fetch_add(1, Ordering::Relaxed)
+        ),
+        iat,
+        ..Default::default()
+    }
+}
+
+/// 当前有效、剩余 `ttl` 秒的票据
+pub fn valid_ticket(src_id: &str, dst_id: &str, ttl: i64) -> String {
+    signer().sign(&payload(src_id, dst_id, now(), ttl))
+}
+
+/// 一小时前签发、有效期 60 秒的已过期票据 (超出默认时钟偏差)
+pub fn expired_ticket(src_id: &str, dst_id: &str) -> String {
+    signer().sign(&payload(src_id, dst_id, now() - 3600, 60))
+}
+
+/// 签发给其他设备的有效票据, 用 `dst_id` 验证时返回设备不匹配
+pub fn wrong_device_ticket(src_id: &str, dst_id: &str) -> String {
+    signer().sign(&payload(src_id, &format!("not-{}", dst_id), now(), 60))
+}
+
+/// 由其他私钥签发的票据, 使用样例公钥验证时签名无效
+pub fn bad_signature_ticket(src_id: &str, dst_id: &str) -> String {
+    TicketSigner::from_secret_hex(&hex::encode([0xeeu8; 32]))
+        .expect("私钥无效")
+        .sign(&payload(src_id, dst_id, now(), 60))
+}
+
+fn now() -> i64 {
+    unix_time(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticket::TicketError;
+
+    #[test]
+    fn test_fixtures() {
+        let verifier = verifier();
+        // 公钥固定, 下游快照保持稳定
+        assert_eq!(signer().verifying_key_hex(), FIXTURE_PUBLIC_KEY_HEX);
+        let payload = verifier
+            .verify(&valid_ticket("123456789", "987654321", 60), "987654321")
+            .unwrap();
+        assert_eq!(payload.src_id, "123456789");
+        assert!(matches!(
+            verifier.verify(&expired_ticket("123456789", "987654321"), "987654321"),
+            Err(TicketError::Expired)
+        ));
+        assert!(matches!(
+            verifier.verify(&wrong_device_ticket("123456789", "987654321"), "987654321"),
+            Err(TicketError::DeviceMismatch { .. })
+        ));
+        assert!(matches!(
+            verifier.verify(&bad_signature_ticket("123456789", "987654321"), "987654321"),
+            Err(TicketError::SignatureInvalid)
+        ));
+    }
+}
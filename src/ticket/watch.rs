@@ -0,0 +1,146 @@
+//! 会话期间的票据到期提醒
+//!
+//! 验证器本身不持有定时器; 长连接会话需要在票据过期前换取新票据时, 为已验证的载荷
+//! 创建 [`TicketWatch`], 在会话循环中轮询 [`poll`](TicketWatch::poll), 或启用 `tokio`
+//! feature 后用 [`spawn`](TicketWatch::spawn) 交给后台任务。
+
+use super::{Clock, TicketPayload};
+use std::{sync::Arc, time::Duration};
+
+/// 后台任务两次读取时间源之间的最长间隔 (秒), 系统休眠或时钟调整后也能及时触发
+#[cfg(feature = "tokio")]
+const RECHECK_SECS: i64 = 60;
+
+/// 票据到期提醒: 在 exp 之前 `lead` 时触发一次回调
+pub struct TicketWatch {
+    fire_at: i64,
+    clock: Arc<dyn Clock>,
+    callback: Box<dyn FnMut() + Send>,
+    fired: bool,
+}
+
+impl TicketWatch {
+    /// 为已验证的载荷创建提醒, 在 `exp - lead` 时刻 (按 `clock`) 调用 `callback`
+    pub fn new(
+        payload: &TicketPayload,
+        lead: Duration,
+        clock: Arc<dyn Clock>,
+        callback: impl FnMut() + Send + 'static,
+    ) -> Self {
+        let lead = i64::try_from(lead.as_secs()).unwrap_or(i64::MAX);
+        Self {
+            fire_at: payload.exp.saturating_sub(lead),
+            clock,
+            callback: Box::new(callback),
+            fired: false,
+        }
+    }
+
+    /// 触发时刻 (Unix 秒)
+    pub fn fire_at(&self) -> i64 {
+        self.fire_at
+    }
+
+    /// 是否已经触发过
+    pub fn is_fired(&self) -> bool {
+        self.fired
+    }
+
+    /// 到达触发时刻且尚未触发时调用回调, 返回本次是否触发
+    pub fn poll(&mut self) -> bool {
+        if self.fired || self.clock.now_unix() < self.fire_at {
+            return false;
+        }
+        self.fired = true;
+        (self.callback)();
+        true
+    }
+
+    /// 在 Tokio 后台任务中等待并触发回调 (需启用 `tokio` feature)
+    ///
+    /// 会话提前结束时对返回的句柄调用 `abort` 取消提醒。
+    #[cfg(feature = "tokio")]
+    pub fn spawn(mut self) -> hbb_common::tokio::task::JoinHandle<()> {
+        hbb_common::tokio::spawn(async move {
+            while !self.poll() {
+                let wait = self
+                    .fire_at
+                    .saturating_sub(self.clock.now_unix())
+                    .clamp(1, RECHECK_SECS);
+                hbb_common::tokio::time::sleep(Duration::from_secs(wait as u64)).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicI64, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    struct MockClock(AtomicI64);
+
+    impl Clock for MockClock {
+        fn now_unix(&self) -> i64 {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    fn payload(exp: i64) -> TicketPayload {
+        TicketPayload {
+            exp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ticket_watch_poll() {
+        let clock = Arc::new(MockClock(AtomicI64::new(1_000)));
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = fired.clone();
+        let mut watch = TicketWatch::new(
+            &payload(1_300),
+            Duration::from_secs(60),
+            clock.clone(),
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+        assert_eq!(watch.fire_at(), 1_240);
+
+        clock.0.store(1_239, Ordering::SeqCst);
+        assert!(!watch.poll());
+        clock.0.store(1_240, Ordering::SeqCst);
+        assert!(watch.poll());
+        assert!(watch.is_fired());
+        // 只触发一次
+        clock.0.store(1_500, Ordering::SeqCst);
+        assert!(!watch.poll());
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_ticket_watch_spawn() {
+        let clock = Arc::new(MockClock(AtomicI64::new(1_250)));
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = fired.clone();
+        let watch = TicketWatch::new(
+            &payload(1_300),
+            Duration::from_secs(60),
+            clock,
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+        let rt = hbb_common::tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        rt.block_on(async { watch.spawn().await }).unwrap();
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+    }
+}
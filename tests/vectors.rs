@@ -0,0 +1,166 @@
+//! 票据规范化测试向量
+//!
+//! 固定私钥和载荷签发的票据必须与这里的常量逐字节一致, 独立实现的签发端 (如 Go 版
+//! API Server) 用同一组向量核对序列化和签名。向量发生变化意味着票据格式不再兼容,
+//! 只能作为经过评审的有意变更更新。
+
+use librustdesk::ticket::{Clock, TicketPayload, TicketSigner, TicketVerifier};
+
+/// 向量私钥种子 (十六进制)
+const SECRET_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+/// 向量公钥 (十六进制), 与 [`SECRET_HEX`] 对应
+const PUBLIC_KEY_HEX: &str = "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8";
+const NOW: i64 = 1_700_000_000;
+const DEVICE_ID: &str = "987654321";
+
+const BASIC: &str = concat!(
+    "TICKET:v1:",
+    "eyJkc3RfaWQiOiI5ODc2NTQzMjEiLCJleHAiOjE3MDAwMDAzMDAsImlhdCI6MTcwMDAwMDAwMCwibm9u",
+    "Y2UiOiIwMTIzNDU2Nzg5YWJjZGVmIiwic3JjX2lkIjoiMTIzNDU2Nzg5In0",
+    ".",
+    "UscwIT1HeCuHhP0Xa5PuAXj0lald1Y6YNsdd_yh7RyTEXlfs2YFv5qZ0eSwtdJRq8umZU3K35-oKueAm",
+    "f_L4BQ",
+);
+const WITH_NBF: &str = concat!(
+    "TICKET:v1:",
+    "eyJkc3RfaWQiOiI5ODc2NTQzMjEiLCJleHAiOjE3MDAwMDAzMDAsImlhdCI6MTcwMDAwMDAwMCwibmJm",
+    "IjoxNjk5OTk5OTkwLCJub25jZSI6IjAxMjM0NTY3ODlhYmNkZWYiLCJzcmNfaWQiOiIxMjM0NTY3ODki",
+    "fQ",
+    ".",
+    "Z8s6PxINKCDOxtYUIxoUGasBffUC4WijIXvZG_gF0Db5KX9ga6jpfphaFruATqr9UpoP-8lGvh2BbfxQ",
+    "H1lYCw",
+);
+const WITH_SCOPES: &str = concat!(
+    "TICKET:v1:",
+    "eyJkc3RfaWQiOiI5ODc2NTQzMjEiLCJleHAiOjE3MDAwMDAzMDAsImlhdCI6MTcwMDAwMDAwMCwibm9u",
+    "Y2UiOiIwMTIzNDU2Nzg5YWJjZGVmIiwic2NvcGVzIjpbInZpZXciLCJmaWxlIl0sInNyY19pZCI6IjEy",
+    "MzQ1Njc4OSJ9",
+    ".",
+    "bHzdBjuQuKXKslacRwseBdr0ILOm701JhPN3c7x2xz5I6YmdUoszoGT0cJMl20HhrP3rb-aKKEuPBQxp",
+    "rF6vAw",
+);
+/// [`BASIC`] 载荷的指纹 ([`TicketPayload::fingerprint`]), 即
+/// `{"dst_id":"987654321","iat":1700000000,"nonce":"0123456789abcdef","src_id":"123456789"}`
+/// 的 SHA-256 前 8 字节
+const BASIC_FINGERPRINT: &str = "52cf74648c0aab42";
+/// 目标设备为数组 `["111111111","site-7-*"]` 时的指纹
+const MULTI_FINGERPRINT: &str = "a090a49a2ce5a088";
+
+struct FixedClock;
+
+impl Clock for FixedClock {
+    fn now_unix(&self) -> i64 {
+        NOW
+    }
+}
+
+fn signer() -> TicketSigner {
+    TicketSigner::from_secret_hex(SECRET_HEX).unwrap()
+}
+
+fn verifier() -> TicketVerifier {
+    let mut verifier = TicketVerifier::new();
+    verifier.set_public_key_hex(PUBLIC_KEY_HEX).unwrap();
+    verifier.with_clock(Box::new(FixedClock));
+    verifier
+}
+
+fn payload() -> TicketPayload {
+    TicketPayload {
+        src_id: "123456789".to_owned(),
+        dst_id: DEVICE_ID.into(),
+        exp: NOW + 300,
+        nonce: "0123456789abcdef".to_owned(),
+        iat: NOW,
+        ..Default::default()
+    }
+}
+
+fn check(payload: &TicketPayload, expected: &str) {
+    let ticket = signer().sign(payload);
+    assert_eq!(ticket, expected);
+    let verified = verifier().verify(expected, DEVICE_ID).unwrap();
+    assert_eq!(
+        serde_json::to_value(&verified).unwrap(),
+        serde_json::to_value(payload).unwrap()
+    );
+}
+
+/// 无标准库的验证核心 (libs/ticket_core) 必须与主程序得出相同结论
+fn check_core(ticket: &str, device_id: &str) -> Result<ticket_core::Claims, ticket_core::Error> {
+    let key: [u8; 32] = hex::decode(PUBLIC_KEY_HEX).unwrap().try_into().unwrap();
+    ticket_core::verify(&key, ticket, device_id, NOW, 30)
+}
+
+#[test]
+fn test_vector_basic() {
+    check(&payload(), BASIC);
+}
+
+#[test]
+fn test_vector_with_nbf() {
+    let payload = TicketPayload {
+        nbf: Some(NOW - 10),
+        ..payload()
+    };
+    check(&payload, WITH_NBF);
+}
+
+#[test]
+fn test_vector_with_scopes() {
+    let payload = TicketPayload {
+        scopes: vec!["view".to_owned(), "file".to_owned()],
+        ..payload()
+    };
+    check(&payload, WITH_SCOPES);
+}
+
+#[test]
+fn test_vector_fingerprint() {
+    let verified = verifier().verify(BASIC, DEVICE_ID).unwrap();
+    assert_eq!(verified.fingerprint(), BASIC_FINGERPRINT);
+    // 只覆盖身份相关字段, 与 exp、scopes 等无关
+    let payload = TicketPayload {
+        exp: NOW + 600,
+        scopes: vec!["view".to_owned()],
+        ..payload()
+    };
+    assert_eq!(payload.fingerprint(), BASIC_FINGERPRINT);
+    let multi = TicketPayload {
+        dst_id: vec!["111111111".to_owned(), "site-7-*".to_owned()].into(),
+        ..payload
+    };
+    assert_eq!(multi.fingerprint(), MULTI_FINGERPRINT);
+}
+
+#[test]
+fn test_vectors_ticket_core() {
+    for vector in [BASIC, WITH_NBF, WITH_SCOPES] {
+        assert_eq!(check_core(vector, DEVICE_ID).unwrap().src_id, "123456789");
+    }
+
+    // 数组和前缀模式的目标设备
+    let multi = TicketPayload {
+        dst_id: vec!["111111111".to_owned(), "site-7-*".to_owned()].into(),
+        ..payload()
+    };
+    let ticket = signer().sign(&multi);
+    for device_id in ["111111111", "site-7-042", "site-8-042"] {
+        let native = verifier().verify(&ticket, device_id).map_err(|e| e.code());
+        let core = check_core(&ticket, device_id).map_err(|e| e.code());
+        assert_eq!(native.err(), core.err(), "{}", device_id);
+    }
+    assert!(check_core(&ticket, "site-7-042").is_ok());
+
+    // 签发时间晚于当前时间
+    let future = TicketPayload {
+        iat: NOW + 60,
+        exp: NOW + 120,
+        ..payload()
+    };
+    let ticket = signer().sign(&future);
+    assert_eq!(
+        verifier().verify(&ticket, DEVICE_ID).unwrap_err().code(),
+        check_core(&ticket, DEVICE_ID).unwrap_err().code()
+    );
+}